@@ -0,0 +1,256 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::bitboard::BitBoard;
+use crate::entities::{Board, Edge, MinMax, Player};
+use crate::error::{Error, Result};
+use crate::search::{self, SearchContext};
+use crate::solver::Solver;
+use crate::transposition::TranspositionTable;
+
+// One legal move with its value for the player to move and the line of play
+// the search expects to follow it, starting with the reply.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MoveAnalysis {
+    edge: Edge,
+    value: i32,
+    variation: Vec<Edge>,
+}
+
+impl MoveAnalysis {
+    pub fn new(_edge: Edge, _value: i32, _variation: Vec<Edge>) -> MoveAnalysis {
+        MoveAnalysis {
+            edge: _edge,
+            value: _value,
+            variation: _variation,
+        }
+    }
+
+    pub fn get_edge(&self) -> Edge {
+        self.edge
+    }
+
+    pub fn get_value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn get_variation(&self) -> &[Edge] {
+        &self.variation
+    }
+}
+
+// Every legal move of a position, best first. The values are box margins for
+// the player to move, their boxes minus the opponent's. When few enough edges
+// are left they are exact final margins from the solver, otherwise they are the
+// margins the deepest MinMax search which finished in time expects after the
+// moves it looked at, boxes still free not counted.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    player: Player,
+    moves: Vec<MoveAnalysis>,
+    exact: bool,
+    depth: u8,
+    nodes: u64,
+    elapsed: Duration,
+}
+
+impl Analysis {
+    pub fn run(board: &Board, time_limit: Duration, max_depth: u8) -> Result<Analysis> {
+        Analysis::run_with_stop(board, time_limit, max_depth, &AtomicBool::new(false))
+    }
+
+    // When the solver cannot finish in time the position is searched instead,
+    // at least one level deep.
+    pub fn run_with_stop(board: &Board, time_limit: Duration, max_depth: u8, stop: &AtomicBool) -> Result<Analysis> {
+        if board.is_complete() {
+            return Err(Error::GameOver);
+        }
+
+        let start = Instant::now();
+        let deadline = search::deadline_after(time_limit);
+        let mut solver = Solver::default();
+        if solver.can_solve(board) {
+            match Analysis::solve(board, &mut solver, start, deadline, stop) {
+                Err(Error::Stopped) if !stop.load(Ordering::Relaxed) => (),
+                result => return result,
+            }
+        }
+
+        Ok(Analysis::search(board, start, deadline, max_depth, stop))
+    }
+
+    fn solve(board: &Board, solver: &mut Solver, start: Instant, deadline: Option<Instant>, stop: &AtomicBool)
+            -> Result<Analysis> {
+        let mut moves = Vec::new();
+
+        for (edge, margin) in solver.solve_moves_with_stop(board, deadline, stop)? {
+            let mut next = board.clone();
+            next.play(edge)?;
+
+            let mut variation = Vec::new();
+            while !next.is_complete() {
+                let (reply, _) = solver.best_move_with_stop(&next, deadline, stop)?;
+                next.play(reply)?;
+                variation.push(reply);
+            }
+
+            moves.push(MoveAnalysis::new(edge, margin, variation));
+        }
+
+        Ok(Analysis::new(board.get_current_player(), moves, true, board.get_free_edges().len() as u8,
+            solver.get_nodes(), start.elapsed()))
+    }
+
+    fn search(board: &Board, start: Instant, deadline: Option<Instant>, max_depth: u8, stop: &AtomicBool) -> Analysis {
+        let player = board.get_current_player();
+        let mut bitboard = BitBoard::from_board(board);
+        let mut table = TranspositionTable::default();
+        let mut nodes = 0;
+
+        // every move gets at least a one level search, whatever the time limit
+        let mut moves = Vec::new();
+        let mut depth = 0;
+
+        let remaining = bitboard.get_free_edges().len();
+        for level in 1..=remaining.min(max_depth.max(1) as usize) as u8 {
+            let mut context = SearchContext::with_stop(&mut table, if level == 1 {
+                    None
+                } else {
+                    deadline
+                }, stop);
+            context.set_margin_evaluation(true);
+            let level_moves = Analysis::search_level(&mut bitboard, &mut context, level);
+            nodes += context.get_nodes();
+
+            if context.is_aborted() {
+                break;
+            }
+
+            moves = level_moves;
+            depth = level;
+
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+        }
+
+        Analysis::new(player, moves, false, depth, nodes, start.elapsed())
+    }
+
+    fn search_level(board: &mut BitBoard, context: &mut SearchContext, depth: u8) -> Vec<MoveAnalysis> {
+        let player = board.get_current_player();
+        let mut moves = Vec::new();
+
+        for edge in board.get_free_edges() {
+            board.make_move(edge);
+            let is_max = board.get_current_player() == Player::Player2;
+            let (_, value) = MinMax::search(board, context, depth - 1, i32::MIN, i32::MAX, is_max);
+            let variation = Analysis::get_variation(board, context, depth - 1);
+            board.unmake_move();
+
+            if context.is_aborted() {
+                break;
+            }
+
+            // the margin is player 2's, so it is turned around for player 1
+            let value = if player == Player::Player2 {
+                value
+            } else {
+                -value
+            };
+            moves.push(MoveAnalysis::new(board.get_edge(edge), value, variation));
+        }
+
+        moves
+    }
+
+    // Follows the best moves stored in the table for as long as they are there.
+    fn get_variation(board: &mut BitBoard, context: &mut SearchContext, depth: u8) -> Vec<Edge> {
+        let mut variation = Vec::new();
+
+        while variation.len() < depth as usize && !board.is_complete() {
+            let best = context.probe(board.get_hash()).and_then(|entry| entry.get_best());
+            match best {
+                Some(edge) if !board.is_drawn(edge) => {
+                    board.make_move(edge);
+                    variation.push(board.get_edge(edge));
+                }
+                _ => break,
+            }
+        }
+
+        for _ in 0..variation.len() {
+            board.unmake_move();
+        }
+
+        variation
+    }
+
+    fn new(_player: Player, mut _moves: Vec<MoveAnalysis>, _exact: bool, _depth: u8, _nodes: u64, _elapsed: Duration)
+            -> Analysis {
+        _moves.sort_by_key(|analysis| Reverse(analysis.get_value()));
+
+        Analysis {
+            player: _player,
+            moves: _moves,
+            exact: _exact,
+            depth: _depth,
+            nodes: _nodes,
+            elapsed: _elapsed,
+        }
+    }
+
+    pub fn get_player(&self) -> Player {
+        self.player
+    }
+
+    pub fn get_moves(&self) -> &[MoveAnalysis] {
+        &self.moves
+    }
+
+    pub fn get_best(&self) -> Option<&MoveAnalysis> {
+        self.moves.first()
+    }
+
+    pub fn get_move(&self, edge: Edge) -> Option<&MoveAnalysis> {
+        self.moves.iter().find(|analysis| analysis.get_edge() == edge)
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    pub fn get_depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn get_nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn get_elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+// A table with one move per line, e.g.
+//
+//     v0,1    2  h1,0 v0,2
+//     h1,0   -1  v0,1
+//     depth 5, 1520 nodes, 3 ms
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for analysis in &self.moves {
+            let variation: Vec<String> = analysis.get_variation().iter().map(|edge| edge.to_string()).collect();
+            writeln!(f, "{:<6} {:>4}  {}", analysis.get_edge().to_string(), analysis.get_value(), variation.join(" "))?;
+        }
+
+        write!(f, "{} {}, {} nodes, {} ms", if self.exact {
+                "exact, depth"
+            } else {
+                "depth"
+            }, self.depth, self.nodes, self.elapsed.as_millis())
+    }
+}
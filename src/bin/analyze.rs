@@ -0,0 +1,39 @@
+use std::env;
+use std::process;
+use std::time::Duration;
+
+use dotsnboxes::analysis::Analysis;
+use dotsnboxes::entities::{Board, DEFAULT_TIME_LIMIT_MILLIS};
+
+const USAGE: &str = "usage: analyze <position> [time limit in ms] [max depth]";
+
+fn parse_arg<T: std::str::FromStr>(args: &[String], index: usize, default: T) -> Result<T, String> {
+    match args.get(index) {
+        Some(value) => value.parse().map_err(|_| format!("'{}' is not a number\n{}", value, USAGE)),
+        None => Ok(default),
+    }
+}
+
+// Prints every move of a position with its box margin and expected continuation, e.g.
+//
+//     analyze "3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2" 500
+fn run(args: &[String]) -> Result<Analysis, String> {
+    let position = args.first().ok_or_else(|| String::from(USAGE))?;
+    let board = Board::from_position_str(position).map_err(|e| e.to_string())?;
+    let time_limit = parse_arg(args, 1, DEFAULT_TIME_LIMIT_MILLIS)?;
+    let max_depth = parse_arg(args, 2, u8::MAX)?;
+
+    Analysis::run(&board, Duration::from_millis(time_limit), max_depth).map_err(|e| e.to_string())
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(analysis) => println!("{}", analysis),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
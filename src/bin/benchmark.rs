@@ -0,0 +1,102 @@
+use std::env;
+use std::process;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use dotsnboxes::bitboard::BitBoard;
+use dotsnboxes::entities::{Board, MinMax, Player};
+use dotsnboxes::parallel::{self, ParallelSearch};
+use dotsnboxes::search::SearchContext;
+use dotsnboxes::transposition::TranspositionTable;
+
+const USAGE: &str = "usage: benchmark [depth] [max threads]";
+
+const DEFAULT_DEPTH: u8 = 6;
+
+// An empty board, an opening and a middle game position.
+const POSITIONS: [&str; 3] = [
+    "4x4|||1",
+    "4x4|h0,0=1 v1,2=2 h3,1=1 v2,0=2||1",
+    "5x4|h0,0=1 h0,3=2 v1,1=1 v2,4=2 h2,2=1 v0,2=2 h3,0=1 v1,3=2||1",
+];
+
+fn parse_arg<T: std::str::FromStr>(args: &[String], index: usize, default: T) -> Result<T, String> {
+    match args.get(index) {
+        Some(value) => value.parse().map_err(|_| format!("'{}' is not a number\n{}", value, USAGE)),
+        None => Ok(default),
+    }
+}
+
+// The thread counts to try: 1, 2, 4, ... up to the maximum, which is always included.
+fn get_thread_counts(max_threads: usize) -> Vec<usize> {
+    let mut counts: Vec<usize> = (0..).map(|shift| 1 << shift).take_while(|threads| *threads < max_threads).collect();
+    counts.push(max_threads.max(1));
+    counts
+}
+
+fn benchmark(position: &str, depth: u8, thread_counts: &[usize]) -> Result<(), String> {
+    let board = Board::from_position_str(position).map_err(|e| e.to_string())?;
+    let bitboard = BitBoard::from_board(&board);
+    println!("{} at depth {}", position, depth);
+
+    let start = Instant::now();
+    let mut table = TranspositionTable::default();
+    let mut context = SearchContext::new(&mut table, None);
+    let is_max = board.get_current_player() == Player::Player2;
+    let (_, serial_value) = MinMax::search(&mut bitboard.clone(), &mut context, depth, i32::MIN, i32::MAX, is_max);
+    let serial_time = start.elapsed();
+    let nodes = context.get_nodes();
+    print_row("serial", serial_time, nodes, (table.get_probes(), table.get_hit_rate()), serial_time, true);
+
+    for threads in thread_counts {
+        let mut search = ParallelSearch::new(*threads);
+        let start = Instant::now();
+        let (_, value) = search.search(&bitboard, depth, None, &AtomicBool::new(false))
+            .ok_or_else(|| String::from("the search was stopped"))?;
+        let time = start.elapsed();
+
+        let table = search.get_table();
+        print_row(&format!("{} threads", threads), time, search.get_nodes(), (table.get_probes(), table.get_hit_rate()),
+            serial_time, value == serial_value);
+    }
+
+    println!();
+    Ok(())
+}
+
+// The table is described by the number of probes and the share of them which hit.
+fn print_row(name: &str, time: Duration, nodes: u64, (probes, hit_rate): (u64, f64), serial_time: Duration,
+        matches: bool) {
+    println!("  {:<12} {:>8} ms {:>12} nodes {:>12} probes {:>5.1}% hits  {:>5.2}x  {}", name, time.as_millis(),
+        nodes, probes, hit_rate * 100.0, serial_time.as_secs_f64() / time.as_secs_f64().max(1e-9),
+        if matches {
+            "same value"
+        } else {
+            "DIFFERENT VALUE"
+        });
+}
+
+// Searches a few positions to a fixed depth, first serially and then with a
+// growing number of threads, and prints the time each search took, e.g.
+//
+//     cargo run --release --bin benchmark -- 6 8
+fn run(args: &[String]) -> Result<(), String> {
+    let depth = parse_arg(args, 0, DEFAULT_DEPTH)?;
+    let max_threads = parse_arg(args, 1, parallel::get_available_threads())?;
+    let thread_counts = get_thread_counts(max_threads);
+
+    for position in POSITIONS.iter() {
+        benchmark(position, depth, &thread_counts)?;
+    }
+
+    Ok(())
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(e) = run(&args) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,124 @@
+use std::env;
+use std::process;
+
+use dotsnboxes::ratings::Ratings;
+use dotsnboxes::tournament::{self, EngineConfig, PairingResult, Tournament};
+
+const USAGE: &str = "usage: tournament [--size <width>x<height>]... [--games <count>] [--format csv|json] \
+    [--ratings <file>] <engine[:depth][@ms][/threads]> <engine[:depth][@ms][/threads]>...";
+
+const DEFAULT_SIZE: (usize, usize) = (3, 3);
+const DEFAULT_GAMES: u32 = 10;
+
+struct Options {
+    sizes: Vec<(usize, usize)>,
+    games: u32,
+    json: bool,
+    ratings: Option<String>,
+    engines: Vec<EngineConfig>,
+}
+
+fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("'{}' is not a board size\n{}", value, USAGE);
+    let index = value.find('x').ok_or_else(invalid)?;
+    let width = value[..index].parse().map_err(|_| invalid())?;
+    let height = value[index + 1..].parse().map_err(|_| invalid())?;
+    Ok((width, height))
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        sizes: Vec::new(),
+        games: DEFAULT_GAMES,
+        json: false,
+        ratings: None,
+        engines: Vec::new(),
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value\n{}", arg, USAGE));
+
+        match arg.as_str() {
+            "--size" => options.sizes.push(parse_size(value()?)?),
+            "--games" => {
+                let games = value()?;
+                options.games = games.parse().map_err(|_| format!("'{}' is not a number\n{}", games, USAGE))?;
+            }
+            "--format" => match value()?.as_str() {
+                "csv" => options.json = false,
+                "json" => options.json = true,
+                format => return Err(format!("unknown format '{}'\n{}", format, USAGE)),
+            },
+            "--ratings" => options.ratings = Some(value()?.clone()),
+            config => {
+                let engine: EngineConfig = config.parse().map_err(|e| format!("{}\n{}", e, USAGE))?;
+                // the ratings and the results could not tell two engines of the same
+                // configuration apart
+                if options.engines.contains(&engine) {
+                    return Err(format!("engine '{}' is given twice\n{}", engine, USAGE));
+                }
+                options.engines.push(engine);
+            }
+        }
+    }
+
+    if options.engines.len() < 2 {
+        return Err(String::from(USAGE));
+    }
+    if options.sizes.is_empty() {
+        options.sizes.push(DEFAULT_SIZE);
+    }
+
+    Ok(options)
+}
+
+// Rates the engines under their configurations in the given file, which is
+// created if it is not there yet.
+fn update_ratings(path: &str, engines: &[EngineConfig], results: &[PairingResult]) -> dotsnboxes::error::Result<()> {
+    let mut ratings = Ratings::load_or_new(path)?;
+    let index = |config: &EngineConfig| engines.iter().position(|engine| engine == config);
+
+    // every pairing shows up from both sides, so only one of them is counted;
+    // parse_options makes sure no two engines share a configuration
+    for result in results.iter().filter(|result| index(result.get_engine()) < index(result.get_opponent())) {
+        ratings.add_pairing(result);
+    }
+
+    ratings.save(path)
+}
+
+// Plays every engine against every other one, e.g.
+//
+//     tournament --size 3x3 --size 4x4 --games 20 minmax:4 minmax:6 greedy random
+fn run(args: &[String]) -> Result<String, String> {
+    let options = parse_options(args)?;
+    let mut results: Vec<PairingResult> = Vec::new();
+
+    for (width, height) in &options.sizes {
+        let tournament = Tournament::new(options.engines.clone(), *width, *height, options.games);
+        results.extend(tournament.run().map_err(|e| e.to_string())?);
+    }
+
+    if let Some(path) = &options.ratings {
+        update_ratings(path, &options.engines, &results).map_err(|e| e.to_string())?;
+    }
+
+    Ok(if options.json {
+        tournament::to_json(&results)
+    } else {
+        tournament::to_csv(&results)
+    })
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(output) => println!("{}", output),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use std::sync::Arc;
+
+use crate::entities::{Board, Edge, Orientation, Player, Square};
+use crate::transposition::Zobrist;
+
+const WORD_BITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new(size: usize) -> BitSet {
+        BitSet {
+            words: vec![0; size.div_ceil(WORD_BITS)],
+        }
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    pub fn insert(&mut self, index: usize) {
+        self.words[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.words[index / WORD_BITS] &= !(1 << (index % WORD_BITS));
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn get_words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Change {
+    edge: usize,
+    player: Player,
+    captured: [usize; 2],
+    captured_count: usize,
+}
+
+// A compact copy of a Board for the search. Edges are numbered like
+// Board::get_edge_index and boxes row by row, each player owning a bit mask of
+// boxes. Moves are applied in place with make_move and taken back with
+// unmake_move, so nothing has to be cloned while searching.
+#[derive(Debug, Clone)]
+pub struct BitBoard {
+    width: usize,
+    height: usize,
+    edges: Vec<Edge>,
+    edge_squares: Vec<Vec<usize>>,
+    square_edges: Vec<[usize; 4]>,
+    drawn: BitSet,
+    squares_1: BitSet,
+    squares_2: BitSet,
+    current: Player,
+    changes: Vec<Change>,
+    zobrist: Arc<Zobrist>,
+    hash: u64,
+}
+
+impl BitBoard {
+    pub fn from_board(board: &Board) -> BitBoard {
+        let edges = board.get_edges();
+        let cols = board.get_cols();
+
+        let edge_squares = edges.iter()
+            .map(|edge| board.get_adjacent_squares(edge).iter()
+                .map(|square| square.get_row() * cols + square.get_col())
+                .collect())
+            .collect();
+
+        let square_edges: Vec<[usize; 4]> = board.get_squares().iter()
+            .map(|square| {
+                let sides = square.get_edges();
+                [
+                    board.get_edge_index(&sides[0]),
+                    board.get_edge_index(&sides[1]),
+                    board.get_edge_index(&sides[2]),
+                    board.get_edge_index(&sides[3]),
+                ]
+            })
+            .collect();
+
+        let mut drawn = BitSet::new(edges.len());
+        for line in board.get_lines() {
+            drawn.insert(board.get_edge_index(&line.get_edge()));
+        }
+
+        let mut squares_1 = BitSet::new(board.get_squares().len());
+        for square in board.get_marked_by_player_1() {
+            squares_1.insert(square.get_row() * cols + square.get_col());
+        }
+
+        let mut squares_2 = BitSet::new(board.get_squares().len());
+        for square in board.get_marked_by_player_2() {
+            squares_2.insert(square.get_row() * cols + square.get_col());
+        }
+
+        let zobrist = Arc::new(Zobrist::new(board.get_width(), board.get_height()));
+
+        let mut bitboard = BitBoard {
+            width: board.get_width(),
+            height: board.get_height(),
+            edges,
+            edge_squares,
+            square_edges,
+            drawn,
+            squares_1,
+            squares_2,
+            current: board.get_current_player(),
+            changes: Vec::new(),
+            zobrist,
+            hash: 0,
+        };
+        bitboard.hash = bitboard.compute_hash();
+        bitboard
+    }
+
+    fn compute_hash(&self) -> u64 {
+        let mut hash = self.zobrist.get_board();
+
+        for edge in 0..self.edges.len() {
+            if self.drawn.contains(edge) {
+                hash ^= self.zobrist.get_edge(edge);
+            }
+        }
+
+        for square in 0..self.square_edges.len() {
+            if self.squares_1.contains(square) {
+                hash ^= self.zobrist.get_square_1(square);
+            } else if self.squares_2.contains(square) {
+                hash ^= self.zobrist.get_square_2(square);
+            }
+        }
+
+        if self.current == Player::Player2 {
+            hash ^= self.zobrist.get_player_2();
+        }
+
+        hash
+    }
+
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn get_square_count(&self) -> usize {
+        self.square_edges.len()
+    }
+
+    pub fn get_edge(&self, index: usize) -> Edge {
+        self.edges[index]
+    }
+
+    // The same numbering as Board::get_edge_index.
+    pub fn get_edge_index(&self, edge: &Edge) -> usize {
+        match edge.get_orientation() {
+            Orientation::Horizontal => edge.get_row() * (self.width - 1) + edge.get_col(),
+            Orientation::Vertical => self.height * (self.width - 1) + edge.get_row() * self.width + edge.get_col(),
+        }
+    }
+
+    pub fn get_square(&self, index: usize) -> Square {
+        let cols = self.width - 1;
+        Square::new(index / cols, index % cols)
+    }
+
+    pub fn get_drawn(&self) -> &BitSet {
+        &self.drawn
+    }
+
+    pub fn is_drawn(&self, index: usize) -> bool {
+        self.drawn.contains(index)
+    }
+
+    pub fn get_current_player(&self) -> Player {
+        self.current
+    }
+
+    pub fn set_current_player(&mut self, player: Player) {
+        // the side to move key is part of the hash only while player 2 is to move
+        if (self.current == Player::Player2) != (player == Player::Player2) {
+            self.hash ^= self.zobrist.get_player_2();
+        }
+        self.current = player;
+    }
+
+    pub fn get_score(&self, player: Player) -> usize {
+        match player {
+            Player::Player1 => self.squares_1.count(),
+            Player::Player2 => self.squares_2.count(),
+            Player::Dummy => 0,
+        }
+    }
+
+    pub fn get_owner(&self, square: usize) -> Player {
+        if self.squares_1.contains(square) {
+            Player::Player1
+        } else if self.squares_2.contains(square) {
+            Player::Player2
+        } else {
+            Player::Dummy
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.get_score(Player::Player1) + self.get_score(Player::Player2) == self.get_square_count()
+    }
+
+    pub fn get_free_edges(&self) -> Vec<usize> {
+        (0..self.edges.len()).filter(|index| !self.drawn.contains(*index)).collect()
+    }
+
+    pub fn get_adjacent_squares(&self, edge: usize) -> &[usize] {
+        &self.edge_squares[edge]
+    }
+
+    pub fn get_square_edges(&self, square: usize) -> [usize; 4] {
+        self.square_edges[square]
+    }
+
+    pub fn get_drawn_sides(&self, square: usize) -> usize {
+        self.square_edges[square].iter().filter(|edge| self.drawn.contains(**edge)).count()
+    }
+
+    // How many boxes drawing the edge would complete, without drawing it.
+    pub fn get_captures(&self, edge: usize) -> usize {
+        self.edge_squares[edge].iter().filter(|square| self.get_drawn_sides(**square) == 3).count()
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.changes.len()
+    }
+
+    // Draws the edge for the current player and returns how many boxes it
+    // completed. The turn passes to the opponent unless a box was completed.
+    pub fn make_move(&mut self, edge: usize) -> usize {
+        let mut change = Change {
+            edge,
+            player: self.current,
+            captured: [0; 2],
+            captured_count: 0,
+        };
+
+        self.drawn.insert(edge);
+        self.hash ^= self.zobrist.get_edge(edge);
+
+        for i in 0..self.edge_squares[edge].len() {
+            let square = self.edge_squares[edge][i];
+            if self.get_drawn_sides(square) == 4 {
+                change.captured[change.captured_count] = square;
+                change.captured_count += 1;
+
+                if self.current == Player::Player1 {
+                    self.squares_1.insert(square);
+                    self.hash ^= self.zobrist.get_square_1(square);
+                } else {
+                    self.squares_2.insert(square);
+                    self.hash ^= self.zobrist.get_square_2(square);
+                }
+            }
+        }
+
+        if change.captured_count == 0 {
+            self.set_current_player(self.current.get_opponent());
+        }
+
+        self.changes.push(change);
+        change.captured_count
+    }
+
+    pub fn unmake_move(&mut self) {
+        if let Some(change) = self.changes.pop() {
+            self.drawn.remove(change.edge);
+            self.hash ^= self.zobrist.get_edge(change.edge);
+
+            for square in &change.captured[..change.captured_count] {
+                if change.player == Player::Player1 {
+                    self.squares_1.remove(*square);
+                    self.hash ^= self.zobrist.get_square_1(*square);
+                } else {
+                    self.squares_2.remove(*square);
+                    self.hash ^= self.zobrist.get_square_2(*square);
+                }
+            }
+
+            self.set_current_player(change.player);
+        }
+    }
+}
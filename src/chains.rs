@@ -0,0 +1,346 @@
+use crate::bitboard::BitBoard;
+use crate::entities::Player;
+
+// Chains of at least this many boxes and loops of at least this many boxes are long.
+pub const LONG_CHAIN_SIZE: usize = 3;
+pub const LONG_LOOP_SIZE: usize = 4;
+
+// Boxes the controller hands over to keep control at the end of a chain or a loop.
+const CHAIN_SACRIFICE: i32 = 2;
+const LOOP_SACRIFICE: i32 = 4;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ComponentKind {
+    Chain,
+    Loop,
+}
+
+// Boxes with exactly two sides drawn, linked by the free edges they share. The
+// boxes are listed in the order they follow each other. The edges run along
+// them as well: a chain of n boxes has n + 1 edges, the first and the last one
+// leading out of it, while a loop of n boxes has n edges.
+#[derive(Debug, Clone)]
+pub struct Component {
+    kind: ComponentKind,
+    squares: Vec<usize>,
+    edges: Vec<usize>,
+}
+
+impl Component {
+    pub fn get_kind(&self) -> ComponentKind {
+        self.kind
+    }
+
+    pub fn get_squares(&self) -> &[usize] {
+        &self.squares
+    }
+
+    pub fn get_edges(&self) -> &[usize] {
+        &self.edges
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.squares.len()
+    }
+
+    pub fn is_long(&self) -> bool {
+        match self.kind {
+            ComponentKind::Chain => self.get_size() >= LONG_CHAIN_SIZE,
+            ComponentKind::Loop => self.get_size() >= LONG_LOOP_SIZE,
+        }
+    }
+
+    // The edges leading out of a chain. Loops have none.
+    fn get_ends(&self) -> Vec<usize> {
+        match self.kind {
+            ComponentKind::Chain => vec![self.edges[0], self.edges[self.edges.len() - 1]],
+            ComponentKind::Loop => Vec::new(),
+        }
+    }
+
+    // The edge giving the fewest chances to the opponent: a single box can be
+    // opened anywhere, two boxes are split in the middle so they cannot be
+    // declined, and longer chains are opened at an end.
+    fn get_opening_edge(&self) -> usize {
+        match self.kind {
+            ComponentKind::Chain if self.get_size() == 2 => self.edges[1],
+            _ => self.edges[0],
+        }
+    }
+}
+
+// A position split into its chains and loops, together with the boxes which
+// can be taken right away.
+#[derive(Debug, Clone)]
+pub struct ChainAnalysis {
+    components: Vec<Component>,
+    capturable: Vec<usize>,
+}
+
+impl ChainAnalysis {
+    pub fn new(board: &BitBoard) -> ChainAnalysis {
+        let square_count = board.get_square_count();
+        let mut visited = vec![false; square_count];
+        let mut components = Vec::new();
+
+        for square in 0..square_count {
+            if !visited[square] && board.get_drawn_sides(square) == 2 {
+                let component = ChainAnalysis::find_component(board, square);
+                for member in &component.squares {
+                    visited[*member] = true;
+                }
+                components.push(component);
+            }
+        }
+
+        ChainAnalysis {
+            components,
+            capturable: (0..square_count).filter(|square| board.get_drawn_sides(*square) == 3).collect(),
+        }
+    }
+
+    fn find_component(board: &BitBoard, start: usize) -> Component {
+        let in_component = |square: Option<usize>| match square {
+            Some(square) => board.get_drawn_sides(square) == 2,
+            None => false,
+        };
+
+        // walk to one end of the chain first, or all the way round a loop
+        let mut first = start;
+        let mut first_edge = get_free_sides(board, start)[0];
+        let mut kind = ComponentKind::Chain;
+        loop {
+            let next = get_neighbour(board, first, first_edge);
+            if !in_component(next) {
+                break;
+            }
+
+            let next = next.unwrap_or(first);
+            first_edge = other_free_side(board, next, first_edge);
+            first = next;
+
+            if first == start {
+                kind = ComponentKind::Loop;
+                break;
+            }
+        }
+
+        let mut squares = Vec::new();
+        let mut edges = Vec::new();
+        let mut square = first;
+        let mut edge = first_edge;
+        if kind == ComponentKind::Chain {
+            edges.push(edge);
+        }
+
+        loop {
+            squares.push(square);
+            edge = other_free_side(board, square, edge);
+            edges.push(edge);
+
+            match get_neighbour(board, square, edge) {
+                Some(next) if in_component(Some(next)) && next != first => square = next,
+                _ => break,
+            }
+        }
+
+        Component {
+            kind,
+            squares,
+            edges,
+        }
+    }
+
+    pub fn get_components(&self) -> &[Component] {
+        &self.components
+    }
+
+    pub fn get_capturable(&self) -> &[usize] {
+        &self.capturable
+    }
+
+    pub fn get_long_chains(&self) -> usize {
+        self.components.iter().filter(|c| c.get_kind() == ComponentKind::Chain && c.is_long()).count()
+    }
+
+    pub fn get_loops(&self) -> usize {
+        self.components.iter().filter(|c| c.get_kind() == ComponentKind::Loop).count()
+    }
+
+    pub fn is_all_long(&self) -> bool {
+        self.components.iter().all(|c| c.is_long())
+    }
+
+    // How many boxes more than the opponent the player in control ends up with
+    // when the components are all long and get opened one after another: every
+    // chain costs two boxes and every loop four, except for the last one, which
+    // the controller takes completely.
+    pub fn get_control_value(&self) -> i32 {
+        control_value(self.components.iter())
+    }
+
+    // The player the long chain rule favours: the first player wants the number
+    // of dots plus the number of long chains to be even, the second one odd.
+    pub fn get_favoured_player(&self, dots: usize) -> Player {
+        if (dots + self.get_long_chains()).is_multiple_of(2) {
+            Player::Player1
+        } else {
+            Player::Player2
+        }
+    }
+}
+
+fn control_value<'a, I: Iterator<Item = &'a Component>>(components: I) -> i32 {
+    let mut value = 0;
+    let mut has_chain = false;
+    let mut has_loop = false;
+
+    for component in components {
+        value += component.get_size() as i32;
+        match component.get_kind() {
+            ComponentKind::Chain => {
+                value -= 2 * CHAIN_SACRIFICE;
+                has_chain = true;
+            }
+            ComponentKind::Loop => {
+                value -= 2 * LOOP_SACRIFICE;
+                has_loop = true;
+            }
+        }
+    }
+
+    if has_chain {
+        value + 2 * CHAIN_SACRIFICE
+    } else if has_loop {
+        value + 2 * LOOP_SACRIFICE
+    } else {
+        value
+    }
+}
+
+fn get_free_sides(board: &BitBoard, square: usize) -> Vec<usize> {
+    board.get_square_edges(square).iter().cloned().filter(|edge| !board.is_drawn(*edge)).collect()
+}
+
+fn other_free_side(board: &BitBoard, square: usize, edge: usize) -> usize {
+    get_free_sides(board, square).into_iter().find(|side| *side != edge).unwrap_or(edge)
+}
+
+// The box on the other side of the edge, if it is not on the border.
+fn get_neighbour(board: &BitBoard, square: usize, edge: usize) -> Option<usize> {
+    board.get_adjacent_squares(edge).iter().cloned().find(|other| *other != square)
+}
+
+// Free edges which neither complete a box nor draw the third side of one.
+pub fn get_safe_moves(board: &BitBoard) -> Vec<usize> {
+    board.get_free_edges().into_iter()
+        .filter(|edge| board.get_adjacent_squares(*edge).iter().all(|square| board.get_drawn_sides(*square) < 2))
+        .collect()
+}
+
+// The moves an endgame player picks from, all equally good as far as it can
+// tell. Boxes are taken while there is nothing to lose. Once only loony moves
+// are left, the last two boxes of a chain or four of a loop are handed over if
+// keeping control of the long components which remain is worth more. Safe moves
+// are chosen to make the long chain rule favour the player, and when there are
+// none the smallest component is opened first.
+pub fn get_policy_moves(board: &BitBoard) -> Vec<usize> {
+    let free = board.get_free_edges();
+    if free.is_empty() {
+        return free;
+    }
+
+    let analysis = ChainAnalysis::new(board);
+    let safe = get_safe_moves(board);
+    let captures: Vec<usize> = free.iter().cloned().filter(|edge| board.get_captures(*edge) > 0).collect();
+
+    if !captures.is_empty() {
+        if safe.is_empty() {
+            if let Some(edge) = get_double_dealing_move(board, &analysis) {
+                return vec![edge];
+            }
+        }
+
+        return captures;
+    }
+
+    if !safe.is_empty() {
+        return get_parity_moves(board, safe);
+    }
+
+    let mut components: Vec<&Component> = analysis.get_components().iter().collect();
+    components.sort_by_key(|component| (component.is_long(), component.get_kind() == ComponentKind::Chain,
+        component.get_size()));
+
+    match components.first() {
+        Some(component) => vec![component.get_opening_edge()],
+        None => free,
+    }
+}
+
+fn get_parity_moves(board: &BitBoard, safe: Vec<usize>) -> Vec<usize> {
+    let player = board.get_current_player();
+    let dots = board.get_width() * board.get_height();
+    let mut next = board.clone();
+
+    let mut favoured = Vec::new();
+    for edge in &safe {
+        next.make_move(*edge);
+        if ChainAnalysis::new(&next).get_favoured_player(dots) == player {
+            favoured.push(*edge);
+        }
+        next.unmake_move();
+    }
+
+    if favoured.is_empty() {
+        safe
+    } else {
+        favoured
+    }
+}
+
+// The move declining the last boxes of the chain or loop being taken, if there
+// is nothing else left to take and keeping control pays off.
+fn get_double_dealing_move(board: &BitBoard, analysis: &ChainAnalysis) -> Option<usize> {
+    let capturable = analysis.get_capturable();
+    let leads_to_capturable = |edge: &usize| board.get_adjacent_squares(*edge).iter()
+        .any(|square| board.get_drawn_sides(*square) == 3);
+
+    let mut offer = None;
+    for (index, component) in analysis.get_components().iter().enumerate() {
+        let ends = component.get_ends();
+        let open_ends = ends.iter().filter(|edge| leads_to_capturable(edge)).count();
+
+        if open_ends == 0 {
+            continue;
+        }
+
+        // one box left behind a single capturable box: draw the far side of it
+        // and two boxes go for a single line
+        if capturable.len() == 1 && component.get_size() == 1 && open_ends == 1 {
+            let far = ends.into_iter().find(|edge| !leads_to_capturable(edge));
+            offer = far.map(|edge| (index, edge, CHAIN_SACRIFICE));
+        // what is left of a loop: two boxes between two capturable ones, split in the middle
+        } else if capturable.len() == 2 && component.get_size() == 2 && open_ends == 2 {
+            offer = Some((index, component.get_edges()[1], LOOP_SACRIFICE));
+        }
+    }
+
+    let (index, edge, sacrifice) = offer?;
+    let rest: Vec<&Component> = analysis.get_components().iter().enumerate()
+        .filter(|(other, _)| *other != index)
+        .map(|(_, component)| component)
+        .collect();
+
+    if !rest.iter().any(|component| component.is_long()) {
+        return None;
+    }
+
+    // with short components left the control value is not exact, so any long
+    // component is reason enough to keep control
+    if rest.iter().all(|component| component.is_long()) && control_value(rest.into_iter()) <= sacrifice {
+        return None;
+    }
+
+    Some(edge)
+}
@@ -0,0 +1,156 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::engine::{BlunderEngine, Engine, EngineKind};
+use crate::error::{Error, Result};
+use crate::parallel;
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert];
+
+    // The preset of the level. Easy plays greedily and often makes mistakes,
+    // Medium looks a few moves ahead, Hard thinks for a second and Expert
+    // thinks longer and plays the end of the game perfectly. The last two
+    // search on every core.
+    pub fn get_settings(&self) -> DifficultySettings {
+        let mut settings = match self {
+            Difficulty::Easy => DifficultySettings::new(*self, EngineKind::Greedy, Duration::from_millis(100), 1, 0.3),
+            Difficulty::Medium => DifficultySettings::new(*self, EngineKind::MinMax, Duration::from_millis(250), 3, 0.1),
+            Difficulty::Hard => DifficultySettings::new(*self, EngineKind::MinMax, Duration::from_millis(1000), u8::MAX, 0.0),
+            Difficulty::Expert => DifficultySettings::new(*self, EngineKind::Solver, Duration::from_millis(2000), u8::MAX, 0.0),
+        };
+
+        if *self == Difficulty::Hard || *self == Difficulty::Expert {
+            settings.set_threads(parallel::get_available_threads());
+        }
+
+        settings
+    }
+
+    // The level after this one in ALL, wrapping around at the end.
+    pub fn next(&self) -> Difficulty {
+        let index = Difficulty::ALL.iter().position(|difficulty| difficulty == self).unwrap_or(0);
+        Difficulty::ALL[(index + 1) % Difficulty::ALL.len()]
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Difficulty> {
+        Difficulty::ALL.iter()
+            .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(s))
+            .cloned()
+            .ok_or_else(|| Error::UnknownDifficulty(String::from(s)))
+    }
+}
+
+// Everything which makes up a level. The engine, the limits and the chance of
+// a random move start out as the level's preset and can be changed afterwards.
+// The depth limit and the number of threads only apply to MinMax.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DifficultySettings {
+    difficulty: Difficulty,
+    engine: EngineKind,
+    time_limit: Duration,
+    max_depth: u8,
+    threads: usize,
+    blunder_probability: f64,
+}
+
+impl DifficultySettings {
+    pub fn new(_difficulty: Difficulty, _engine: EngineKind, _time_limit: Duration, _max_depth: u8,
+            _blunder_probability: f64) -> DifficultySettings {
+        DifficultySettings {
+            difficulty: _difficulty,
+            engine: _engine,
+            time_limit: _time_limit,
+            max_depth: _max_depth,
+            threads: 1,
+            blunder_probability: _blunder_probability,
+        }
+    }
+
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    // Switches to the preset of another level, but keeps the engine.
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        let engine = self.engine;
+        *self = difficulty.get_settings();
+        self.engine = engine;
+    }
+
+    pub fn get_engine(&self) -> EngineKind {
+        self.engine
+    }
+
+    pub fn set_engine(&mut self, engine: EngineKind) {
+        self.engine = engine;
+    }
+
+    pub fn get_time_limit(&self) -> Duration {
+        self.time_limit
+    }
+
+    pub fn set_time_limit(&mut self, time_limit: Duration) {
+        self.time_limit = time_limit;
+    }
+
+    pub fn get_max_depth(&self) -> u8 {
+        self.max_depth
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: u8) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn get_blunder_probability(&self) -> f64 {
+        self.blunder_probability
+    }
+
+    pub fn set_blunder_probability(&mut self, blunder_probability: f64) {
+        self.blunder_probability = blunder_probability;
+    }
+
+    pub fn create_engine(&self) -> Box<dyn Engine> {
+        let engine = self.engine.create_with_limits(self.time_limit, self.max_depth, self.threads);
+
+        if self.blunder_probability > 0.0 {
+            Box::new(BlunderEngine::new(engine, self.blunder_probability))
+        } else {
+            engine
+        }
+    }
+}
+
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        Difficulty::default().get_settings()
+    }
+}
@@ -0,0 +1,431 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::bitboard::BitBoard;
+use crate::chains;
+use crate::entities::{Board, Edge, MinMax, Player};
+use crate::error::{Error, Result};
+use crate::mcts::{Budget, Mcts};
+use crate::parallel::ParallelSearch;
+use crate::solver::Solver;
+
+// Anything that can pick a move for a computer player.
+pub trait Engine: Send {
+    fn get_name(&self) -> &str;
+
+    // The edge the engine would draw if it were the player's turn on the board.
+    fn choose_move(&mut self, board: &Board, player: Player) -> Result<Edge>;
+
+    // Like choose_move, but an engine which thinks for a while may give up early
+    // once the stop flag is set. Quick engines simply ignore the flag.
+    fn choose_move_with_stop(&mut self, board: &Board, player: Player, _stop: &AtomicBool) -> Result<Edge> {
+        self.choose_move(board, player)
+    }
+}
+
+fn get_free_edges(board: &Board) -> Result<Vec<Edge>> {
+    let free = board.get_free_edges();
+
+    if free.is_empty() {
+        Err(Error::GameOver)
+    } else {
+        Ok(free)
+    }
+}
+
+impl Engine for MinMax {
+    fn get_name(&self) -> &str {
+        "MinMax"
+    }
+
+    fn choose_move(&mut self, board: &Board, player: Player) -> Result<Edge> {
+        self.choose_move_with_stop(board, player, &AtomicBool::new(false))
+    }
+
+    fn choose_move_with_stop(&mut self, board: &Board, player: Player, stop: &AtomicBool) -> Result<Edge> {
+        let free = get_free_edges(board)?;
+
+        let mut start = board.clone();
+        start.set_current_player(player);
+        let (child, _, _) = if self.get_threads() > 1 {
+            ParallelSearch::new(self.get_threads()).iterative_deepening(&start, self.get_time_limit(),
+                self.get_max_depth(), stop)
+        } else {
+            let (time_limit, max_depth) = (self.get_time_limit(), self.get_max_depth());
+            MinMax::iterative_deepening_with_table(&start, self.get_table(), time_limit, max_depth, stop)
+        };
+
+        // nothing was played if the search got stopped before the first level finished
+        Ok(MinMax::get_move(board, &child).unwrap_or(free[0]))
+    }
+}
+
+// Draws any free edge.
+pub struct RandomEngine {
+    rng: StdRng,
+}
+
+impl RandomEngine {
+    pub fn new() -> RandomEngine {
+        RandomEngine {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn with_seed(seed: u64) -> RandomEngine {
+        RandomEngine {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for RandomEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine for RandomEngine {
+    fn get_name(&self) -> &str {
+        "Random"
+    }
+
+    fn choose_move(&mut self, board: &Board, _player: Player) -> Result<Edge> {
+        let free = get_free_edges(board)?;
+        Ok(*free.choose(&mut self.rng).unwrap_or(&free[0]))
+    }
+}
+
+// Takes the edge completing the most boxes and plays randomly when nothing can be taken.
+pub struct GreedyEngine {
+    rng: StdRng,
+}
+
+impl GreedyEngine {
+    pub fn new() -> GreedyEngine {
+        GreedyEngine {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn with_seed(seed: u64) -> GreedyEngine {
+        GreedyEngine {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for GreedyEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine for GreedyEngine {
+    fn get_name(&self) -> &str {
+        "Greedy"
+    }
+
+    fn choose_move(&mut self, board: &Board, _player: Player) -> Result<Edge> {
+        get_free_edges(board)?;
+        let bitboard = BitBoard::from_board(board);
+
+        let free = bitboard.get_free_edges();
+        let most = free.iter().map(|edge| bitboard.get_captures(*edge)).max().unwrap_or(0);
+        let best: Vec<usize> = free.into_iter().filter(|edge| bitboard.get_captures(*edge) == most).collect();
+
+        Ok(bitboard.get_edge(*best.choose(&mut self.rng).unwrap_or(&best[0])))
+    }
+}
+
+// Takes boxes like the greedy engine, but otherwise never draws the third side
+// of a box as long as there is another choice. When every move gives something
+// away it hands over as few boxes as possible.
+pub struct AvoidThirdSideEngine {
+    rng: StdRng,
+}
+
+impl AvoidThirdSideEngine {
+    pub fn new() -> AvoidThirdSideEngine {
+        AvoidThirdSideEngine {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn with_seed(seed: u64) -> AvoidThirdSideEngine {
+        AvoidThirdSideEngine {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // How many boxes would get their third side from the edge.
+    fn get_third_sides(bitboard: &BitBoard, edge: usize) -> usize {
+        bitboard.get_adjacent_squares(edge).iter()
+            .filter(|square| bitboard.get_drawn_sides(**square) == 2)
+            .count()
+    }
+}
+
+impl Default for AvoidThirdSideEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine for AvoidThirdSideEngine {
+    fn get_name(&self) -> &str {
+        "Avoid third side"
+    }
+
+    fn choose_move(&mut self, board: &Board, _player: Player) -> Result<Edge> {
+        get_free_edges(board)?;
+        let bitboard = BitBoard::from_board(board);
+        let free = bitboard.get_free_edges();
+
+        let captures: Vec<usize> = free.iter().cloned().filter(|edge| bitboard.get_captures(*edge) > 0).collect();
+        let best = if !captures.is_empty() {
+            captures
+        } else {
+            let fewest = free.iter().map(|edge| AvoidThirdSideEngine::get_third_sides(&bitboard, *edge)).min().unwrap_or(0);
+            free.into_iter().filter(|edge| AvoidThirdSideEngine::get_third_sides(&bitboard, *edge) == fewest).collect()
+        };
+
+        Ok(bitboard.get_edge(*best.choose(&mut self.rng).unwrap_or(&best[0])))
+    }
+}
+
+// Plays by the chain analysis: follows the long chain rule while there are safe
+// moves and keeps control in the endgame by declining the last boxes of a chain
+// or loop when that pays off.
+pub struct ChainEngine {
+    rng: StdRng,
+}
+
+impl ChainEngine {
+    pub fn new() -> ChainEngine {
+        ChainEngine {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn with_seed(seed: u64) -> ChainEngine {
+        ChainEngine {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for ChainEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine for ChainEngine {
+    fn get_name(&self) -> &str {
+        "Chains"
+    }
+
+    fn choose_move(&mut self, board: &Board, player: Player) -> Result<Edge> {
+        get_free_edges(board)?;
+        let mut bitboard = BitBoard::from_board(board);
+        bitboard.set_current_player(player);
+
+        let best = chains::get_policy_moves(&bitboard);
+        let edge = best.choose(&mut self.rng).ok_or(Error::GameOver)?;
+
+        Ok(bitboard.get_edge(*edge))
+    }
+}
+
+// Plays perfectly once few enough edges are left for the solver and lets
+// MinMax search before that.
+pub struct SolverEngine {
+    solver: Solver,
+    fallback: MinMax,
+}
+
+impl SolverEngine {
+    pub fn new(solver: Solver, fallback: MinMax) -> SolverEngine {
+        SolverEngine {
+            solver,
+            fallback,
+        }
+    }
+}
+
+impl Default for SolverEngine {
+    fn default() -> Self {
+        SolverEngine::new(Solver::default(), MinMax::new())
+    }
+}
+
+impl Engine for SolverEngine {
+    fn get_name(&self) -> &str {
+        "Solver"
+    }
+
+    fn choose_move(&mut self, board: &Board, player: Player) -> Result<Edge> {
+        self.choose_move_with_stop(board, player, &AtomicBool::new(false))
+    }
+
+    fn choose_move_with_stop(&mut self, board: &Board, player: Player, stop: &AtomicBool) -> Result<Edge> {
+        if !self.solver.can_solve(board) {
+            return self.fallback.choose_move_with_stop(board, player, stop);
+        }
+
+        let mut start = board.clone();
+        start.set_current_player(player);
+        self.solver.best_move_with_stop(&start, None, stop).map(|(edge, _)| edge)
+    }
+}
+
+impl Engine for Mcts {
+    fn get_name(&self) -> &str {
+        "MCTS"
+    }
+
+    fn choose_move(&mut self, board: &Board, player: Player) -> Result<Edge> {
+        self.choose_move_with_stop(board, player, &AtomicBool::new(false))
+    }
+
+    fn choose_move_with_stop(&mut self, board: &Board, player: Player, stop: &AtomicBool) -> Result<Edge> {
+        let mut start = board.clone();
+        start.set_current_player(player);
+
+        let edge = self.search(&start, stop).ok_or(Error::GameOver)?;
+        Ok(board.get_edges()[edge])
+    }
+}
+
+// Lets another engine choose, but now and then plays a random move instead.
+pub struct BlunderEngine {
+    engine: Box<dyn Engine>,
+    probability: f64,
+    rng: StdRng,
+}
+
+impl BlunderEngine {
+    pub fn new(engine: Box<dyn Engine>, probability: f64) -> BlunderEngine {
+        BlunderEngine::with_seed(engine, probability, rand::random())
+    }
+
+    // A probability which is not a number at all never blunders.
+    pub fn with_seed(_engine: Box<dyn Engine>, _probability: f64, seed: u64) -> BlunderEngine {
+        BlunderEngine {
+            engine: _engine,
+            probability: if _probability.is_finite() {
+                    _probability.clamp(0.0, 1.0)
+                } else {
+                    0.0
+                },
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn get_probability(&self) -> f64 {
+        self.probability
+    }
+}
+
+impl Engine for BlunderEngine {
+    fn get_name(&self) -> &str {
+        self.engine.get_name()
+    }
+
+    fn choose_move(&mut self, board: &Board, player: Player) -> Result<Edge> {
+        self.choose_move_with_stop(board, player, &AtomicBool::new(false))
+    }
+
+    fn choose_move_with_stop(&mut self, board: &Board, player: Player, stop: &AtomicBool) -> Result<Edge> {
+        let free = get_free_edges(board)?;
+
+        if self.rng.gen_bool(self.probability) {
+            Ok(*free.choose(&mut self.rng).unwrap_or(&free[0]))
+        } else {
+            self.engine.choose_move_with_stop(board, player, stop)
+        }
+    }
+}
+
+// The engines which can be picked by name, e.g. in the main menu.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EngineKind {
+    MinMax,
+    Random,
+    Greedy,
+    AvoidThirdSide,
+    Chains,
+    Solver,
+    Mcts,
+}
+
+impl EngineKind {
+    pub const ALL: [EngineKind; 7] = [EngineKind::MinMax, EngineKind::Random, EngineKind::Greedy, EngineKind::AvoidThirdSide,
+        EngineKind::Chains, EngineKind::Solver, EngineKind::Mcts];
+
+    pub fn create(&self) -> Box<dyn Engine> {
+        match self {
+            EngineKind::MinMax => Box::new(MinMax::new()),
+            EngineKind::Random => Box::new(RandomEngine::new()),
+            EngineKind::Greedy => Box::new(GreedyEngine::new()),
+            EngineKind::AvoidThirdSide => Box::new(AvoidThirdSideEngine::new()),
+            EngineKind::Chains => Box::new(ChainEngine::new()),
+            EngineKind::Solver => Box::new(SolverEngine::default()),
+            EngineKind::Mcts => Box::new(Mcts::default()),
+        }
+    }
+
+    // Like create, but MinMax, the solver's fallback and MCTS keep to the given
+    // limits. The depth limit and the number of threads only apply to MinMax.
+    pub fn create_with_limits(&self, time_limit: Duration, max_depth: u8, threads: usize) -> Box<dyn Engine> {
+        let mut minmax = MinMax::with_limits(time_limit, max_depth);
+        minmax.set_threads(threads);
+
+        match self {
+            EngineKind::MinMax => Box::new(minmax),
+            EngineKind::Solver => Box::new(SolverEngine::new(Solver::default(), minmax)),
+            EngineKind::Mcts => Box::new(Mcts::with_budget(Budget::Time(time_limit))),
+            kind => kind.create(),
+        }
+    }
+
+    // The kind after this one in ALL, wrapping around at the end.
+    pub fn next(&self) -> EngineKind {
+        let index = EngineKind::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        EngineKind::ALL[(index + 1) % EngineKind::ALL.len()]
+    }
+}
+
+impl fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            EngineKind::MinMax => "minmax",
+            EngineKind::Random => "random",
+            EngineKind::Greedy => "greedy",
+            EngineKind::AvoidThirdSide => "avoid-third-side",
+            EngineKind::Chains => "chains",
+            EngineKind::Solver => "solver",
+            EngineKind::Mcts => "mcts",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for EngineKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<EngineKind> {
+        EngineKind::ALL.iter()
+            .find(|kind| kind.to_string() == s)
+            .cloned()
+            .ok_or_else(|| Error::UnknownEngine(String::from(s)))
+    }
+}
@@ -1,9 +1,23 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
 use ggez::{Context, GameResult};
 use ggez::nalgebra::Point2;
 use ggez::graphics::{self, DrawParam};
 use graphics::{Font, Text, Color};
 use graphics::DrawMode;
 
+use crate::bitboard::BitBoard;
+use crate::difficulty::{Difficulty, DifficultySettings};
+use crate::engine::EngineKind;
+use crate::error::{Error, Result};
+use crate::ratings::Ratings;
+use crate::search::{self, SearchContext};
+use crate::transposition::{Bound, Entry, TranspositionTable};
+
 pub const DELTA: f32 = 0.00001;
 
 pub const WINDOW_WIDTH: f32 = 600.0;
@@ -12,14 +26,15 @@ pub const WINDOW_HEIGHT: f32 = 600.0;
 pub const X_INITIAL_OFFSET: f32 = 60.0;
 pub const Y_INITIAL_OFFSET: f32 = 60.0;
 
-pub const PLAYER_1: &'static str = "P1";
-pub const PLAYER_2: &'static str = "P2";
+pub const PLAYER_1: &str = "P1";
+pub const PLAYER_2: &str = "P2";
 
 pub const PLAYER_1_COLOR: Color = graphics::BLACK;
 pub const PLAYER_2_COLOR: Color = Color::new(255.0, 0.0, 0.0, 255.0);
+pub const HINT_COLOR: Color = Color::new(0.0, 255.0, 0.0, 255.0);
 
-pub const WIDTH: f32 = 3.0;
-pub const HEIGHT: f32 = 3.0;
+pub const WIDTH: usize = 3;
+pub const HEIGHT: usize = 3;
 
 pub struct MathOperations {}
 
@@ -32,6 +47,7 @@ impl MathOperations {
         ((x1 * (y2 - y3) + x2 * (y3 - y1) + x3 * (y1 - y2)) / 2.0).abs()
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn is_inside_triangle(x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32, x: f32, y: f32) -> bool { 
         let a = MathOperations::distance(x1, y1, x2, y2);
         let b = MathOperations::distance(x2, y2, x3, y3);
@@ -45,7 +61,7 @@ impl MathOperations {
         let a1 = MathOperations::area(x, y, x2, y2, x3, y3); 
         let a2 = MathOperations::area(x1, y1, x, y, x3, y3); 
         let a3 = MathOperations::area(x1, y1, x2, y2, x, y); 
-        (a == (a1 + a2 + a3))
+        a == (a1 + a2 + a3)
     }
 
     pub fn is_inside_rectangle(x: f32, y: f32, x_rectangle: f32, y_rectangle: f32, width: f32, height: f32) -> bool {
@@ -57,11 +73,11 @@ impl MathOperations {
         let mut on_y: u8 = 0;
         for point in points {
             if (point.0 - x).abs() <= DELTA {
-                on_x = on_x + 1;
+                on_x += 1;
             }
 
             if (point.1 - y).abs() <= DELTA {
-                on_y = on_y + 1;
+                on_y += 1;
             }
         }
 
@@ -71,7 +87,7 @@ impl MathOperations {
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum State {
-    OnePlayer, TwoPlayers, None, GameOver
+    OnePlayer, TwoPlayers, None, GameOver, Review, ComputerVsComputer, Stats
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -81,40 +97,120 @@ pub enum Player {
     Dummy,
 }
 
+impl Player {
+    pub fn get_opponent(&self) -> Player {
+        match self {
+            Player::Player1 => Player::Player2,
+            Player::Player2 => Player::Player1,
+            Player::Dummy => Player::Dummy,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+// A horizontal edge joins dot (row, col) with dot (row, col + 1),
+// a vertical one joins dot (row, col) with dot (row + 1, col).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Edge {
+    row: usize,
+    col: usize,
+    orientation: Orientation,
+}
+
+impl Edge {
+    pub fn new(_row: usize, _col: usize, _orientation: Orientation) -> Edge {
+        Edge {
+            row: _row,
+            col: _col,
+            orientation: _orientation,
+        }
+    }
+
+    pub fn horizontal(row: usize, col: usize) -> Edge {
+        Edge::new(row, col, Orientation::Horizontal)
+    }
+
+    pub fn vertical(row: usize, col: usize) -> Edge {
+        Edge::new(row, col, Orientation::Vertical)
+    }
+
+    pub fn get_row(&self) -> usize {
+        self.row
+    }
+
+    pub fn get_col(&self) -> usize {
+        self.col
+    }
+
+    pub fn get_orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn get_end(&self) -> (usize, usize) {
+        match self.orientation {
+            Orientation::Horizontal => (self.row, self.col + 1),
+            Orientation::Vertical => (self.row + 1, self.col),
+        }
+    }
+}
+
+// Edges are written as the orientation followed by the zero based row and column
+// of their first dot, e.g. "h0,1" for the top edge of the second box.
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let orientation = match self.orientation {
+            Orientation::Horizontal => 'h',
+            Orientation::Vertical => 'v',
+        };
+        write!(f, "{}{},{}", orientation, self.row, self.col)
+    }
+}
+
+impl FromStr for Edge {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Edge> {
+        let invalid = || Error::InvalidNotation(String::from(s));
+        let mut chars = s.trim().chars();
+
+        let orientation = match chars.next() {
+            Some('h') | Some('H') => Orientation::Horizontal,
+            Some('v') | Some('V') => Orientation::Vertical,
+            _ => return Err(invalid()),
+        };
+
+        let coordinates: Vec<&str> = chars.as_str().split(',').collect();
+        if coordinates.len() != 2 {
+            return Err(invalid());
+        }
+
+        let row = coordinates[0].trim().parse().map_err(|_| invalid())?;
+        let col = coordinates[1].trim().parse().map_err(|_| invalid())?;
+        Ok(Edge::new(row, col, orientation))
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Line {
-    x1: f32,
-    y1: f32,
-    x2: f32,
-    y2: f32,
+    edge: Edge,
     marked_by: Player,
 }
 
 impl Line {
-    pub fn new(_x1: f32, _y1: f32, _x2: f32, _y2: f32, _marked_by: Player) -> Line {
+    pub fn new(_edge: Edge, _marked_by: Player) -> Line {
         Line {
-            x1: _x1,
-            y1: _y1,
-            x2: _x2,
-            y2: _y2,
+            edge: _edge,
             marked_by: _marked_by,
         }
     }
 
-    pub fn get_x1(&self) -> f32 {
-        self.x1
-    }
-    
-    pub fn get_y1(&self) -> f32 {
-        self.y1
-    }
-
-    pub fn get_x2(&self) -> f32 {
-        self.x2
-    }
-
-    pub fn get_y2(&self) -> f32 {
-        self.y2
+    pub fn get_edge(&self) -> Edge {
+        self.edge
     }
 
     pub fn get_marked_by(&self) -> Player {
@@ -124,133 +220,118 @@ impl Line {
 
 impl PartialEq for Line {
     fn eq(&self, other: &Self) -> bool {
-        ((self.x1 - other.x1).abs() <= DELTA &&
-        (self.y1 - other.y1).abs() <= DELTA &&
-        (self.x2 - other.x2).abs() <= DELTA &&
-        (self.y2 - other.y2).abs() <= DELTA) || 
-        ((self.x1 - other.x2).abs() <= DELTA &&
-        (self.y1 - other.y2).abs() <= DELTA &&
-        (self.x2 - other.x1).abs() <= DELTA &&
-        (self.y2 - other.y1).abs() <= DELTA)
+        self.edge == other.edge
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+// The box whose top left corner is dot (row, col).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Square {
-    line1: Line,
-    line2: Line,
-    line3: Line,
-    line4: Line,
+    row: usize,
+    col: usize,
 }
 
 impl Square {
-    pub fn new(_line1: Line, _line2: Line, _line3: Line, _line4: Line) -> Square {
+    pub fn new(_row: usize, _col: usize) -> Square {
         Square {
-            line1: _line1,
-            line2: _line2,
-            line3: _line3,
-            line4: _line4,
+            row: _row,
+            col: _col,
         }
     }
 
-    pub fn get_line1(&self) -> &Line {
-        &self.line1
+    pub fn get_row(&self) -> usize {
+        self.row
     }
 
-    pub fn get_smallest(&self) -> (f32, f32) {
-        let mut smallest_x = std::f32::MAX;
-        let mut smallest_y = std::f32::MAX;
-
-        for x in vec![&self.line1, &self.line2, &self.line3, &self.line4] {
-            if x.get_x1() <= smallest_x {
-                smallest_x = x.get_x1();
-            }
+    pub fn get_col(&self) -> usize {
+        self.col
+    }
 
-            if x.get_x2() <= smallest_x {
-                smallest_x = x.get_x2();
-            }
+    pub fn get_edges(&self) -> [Edge; 4] {
+        [
+            Edge::horizontal(self.row, self.col),
+            Edge::vertical(self.row, self.col + 1),
+            Edge::horizontal(self.row + 1, self.col),
+            Edge::vertical(self.row, self.col),
+        ]
+    }
+}
 
-            if x.get_y1() <= smallest_y {
-                smallest_y = x.get_y1();
-            }
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveOutcome {
+    player: Player,
+    edge: Edge,
+    captured: Vec<Square>,
+}
 
-            if x.get_y2() <= smallest_y {
-                smallest_y = x.get_y2();
-            }
+impl MoveOutcome {
+    pub fn new(_player: Player, _edge: Edge, _captured: Vec<Square>) -> MoveOutcome {
+        MoveOutcome {
+            player: _player,
+            edge: _edge,
+            captured: _captured,
         }
+    }
 
-        (smallest_x, smallest_y)
+    pub fn get_player(&self) -> Player {
+        self.player
     }
-}
 
-impl PartialEq for Square {
-    fn eq(&self, other: &Self) -> bool {
-        (self.line1 == other.line1 || self.line1 == other.line2 || self.line1 == other.line3 || self.line1 == other.line4) &&
-        (self.line2 == other.line1 || self.line2 == other.line2 || self.line2 == other.line3 || self.line2 == other.line4) &&
-        (self.line3 == other.line1 || self.line3 == other.line2 || self.line3 == other.line3 || self.line3 == other.line4) &&
-        (self.line4 == other.line1 || self.line4 == other.line2 || self.line4 == other.line3 || self.line4 == other.line4)
+    pub fn get_edge(&self) -> Edge {
+        self.edge
+    }
+
+    pub fn get_captured(&self) -> &[Square] {
+        &self.captured
+    }
+
+    // Completing a box earns the player another move.
+    pub fn moves_again(&self) -> bool {
+        !self.captured.is_empty()
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Board {
-    width: f32,
-    height: f32,
-    points: Vec::<(f32, f32)>,
-    lines: Vec::<Line>,
-    temp_line: Line,
-    start_x: f32,
-    step_x: f32,
-    start_y: f32, 
-    step_y: f32,
-    squares: Vec::<Square>,
-    marked_squares_by_player_1: Vec::<Square>,
-    marked_squares_by_player_2: Vec::<Square>,
+    width: usize,
+    height: usize,
+    lines: Vec<Line>,
+    drawn: Vec<bool>,
+    squares: Vec<Square>,
+    marked_squares_by_player_1: Vec<Square>,
+    marked_squares_by_player_2: Vec<Square>,
+    current: Player,
+    history: Vec<MoveOutcome>,
+    undone: Vec<MoveOutcome>,
 }
 
 impl Board {
-    pub fn new(_width: f32, _height: f32, window_width: f32, window_height: f32, _start_x: f32, _start_y: f32) -> Board {
-        if _width <= 1.0 || _height <= 1.0 {
-            panic!("Width and height must be greater than 1!");
+    pub fn new(_width: usize, _height: usize) -> Result<Board> {
+        if _width <= 1 || _height <= 1 {
+            return Err(Error::InvalidDimensions(_width, _height));
         }
 
-        let _step_x = (window_width - 2.0 * _start_x) / (_width - 1.0);
-        let _step_y = (window_height - 2.0 * _start_y) / (_height - 1.0);
-        let mut _points = Vec::<(f32, f32)>::new();
         let mut _squares = Vec::<Square>::new();
 
-        for i in 0.._height as u32 {
-            for j in 0.._width as u32 {
-                _points.push((_start_x + (j as f32) * _step_x, _start_y + (i as f32) * _step_y));
+        for i in 0..(_height - 1) {
+            for j in 0..(_width - 1) {
+                // squares are stored row by row, so box (i, j) is at i * (_width - 1) + j
+                _squares.push(Square::new(i, j));
             }
         }
 
-        for i in 0..(_height - 1.0) as u32 {
-            for j in 0..(_width - 1.0) as u32 {
-                let point = _points[(i * _height as u32 + j) as usize];
-                let line1 = Line::new(point.0, point.1,point.0 + _step_x, point.1, Player::Dummy);
-                let line2 = Line::new(point.0 + _step_x, point.1,point.0 + _step_x, point.1 + _step_y, Player::Dummy);
-                let line3 = Line::new(point.0 + _step_x, point.1 + _step_y,point.0, point.1 + _step_y, Player::Dummy);
-                let line4 = Line::new(point.0, point.1 + _step_y,point.0, point.1, Player::Dummy);
-
-                _squares.push(Square::new(line1, line2, line3, line4));
-            }
-        }
-
-        Board {
+        Ok(Board {
             width: _width,
             height: _height,
-            points: _points,
             lines: Vec::new(),
-            temp_line: Line::new(0.0, 0.0, 0.0, 0.0, Player::Dummy),
-            start_x: _start_x,
-            step_x: _step_x,
-            start_y: _start_y,
-            step_y: _step_y,
+            drawn: vec![false; _height * (_width - 1) + (_height - 1) * _width],
             squares: _squares,
             marked_squares_by_player_1: Vec::new(), // player_1 is always you
             marked_squares_by_player_2: Vec::new(), // player_2 is either player_2 or the computer
-        }
+            current: Player::Player1,
+            history: Vec::new(),
+            undone: Vec::new(),
+        })
     }
 
     pub fn get_marked_by_player_1(&self) -> &[Square] {
@@ -261,324 +342,769 @@ impl Board {
         &self.marked_squares_by_player_2
     }
 
-    pub fn get_step_x(&self) -> f32 {
-        self.step_x
+    pub fn get_width(&self) -> usize {
+        self.width
     }
 
-    pub fn get_step_y(&self) -> f32 {
-        self.step_y
+    pub fn get_height(&self) -> usize {
+        self.height
     }
 
-    pub fn get_width(&self) -> f32 {
-        self.width
+    pub fn get_rows(&self) -> usize {
+        self.height - 1
     }
 
-    pub fn get_height(&self) -> f32 {
-        self.height
+    pub fn get_cols(&self) -> usize {
+        self.width - 1
     }
 
-    pub fn get_temp_line(&self) -> Line {
-        self.temp_line
+    pub fn get_current_player(&self) -> Player {
+        self.current
+    }
+
+    pub fn set_current_player(&mut self, player: Player) {
+        self.current = player;
+    }
+
+    pub fn get_lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    pub fn get_squares(&self) -> &[Square] {
+        &self.squares
+    }
+
+    // All edges of the board, horizontal ones first, each group in row-major order.
+    pub fn get_edges(&self) -> Vec<Edge> {
+        let mut edges = Vec::new();
+
+        for i in 0..self.height {
+            for j in 0..(self.width - 1) {
+                edges.push(Edge::horizontal(i, j));
+            }
+        }
+
+        for i in 0..(self.height - 1) {
+            for j in 0..self.width {
+                edges.push(Edge::vertical(i, j));
+            }
+        }
+
+        edges
+    }
+
+    // The edges nobody has drawn yet, in the order of get_edges.
+    pub fn get_free_edges(&self) -> Vec<Edge> {
+        self.get_edges().into_iter().filter(|edge| !self.drawn[self.get_edge_index(edge)]).collect()
     }
 
-    pub fn contains_line(&self, line: &Line) -> bool {
-        self.lines.contains(line)
+    // Horizontal edges come first, matching the order of get_edges.
+    pub fn get_edge_index(&self, edge: &Edge) -> usize {
+        match edge.get_orientation() {
+            Orientation::Horizontal => edge.get_row() * (self.width - 1) + edge.get_col(),
+            Orientation::Vertical => self.height * (self.width - 1) + edge.get_row() * self.width + edge.get_col(),
+        }
     }
 
-    pub fn add_line(&mut self, line: Line) {
+    pub fn contains_edge(&self, edge: &Edge) -> bool {
+        self.is_valid_edge(edge) && self.drawn[self.get_edge_index(edge)]
+    }
+
+    pub fn is_valid_edge(&self, edge: &Edge) -> bool {
+        match edge.get_orientation() {
+            Orientation::Horizontal => edge.get_row() < self.height && edge.get_col() < self.width - 1,
+            Orientation::Vertical => edge.get_row() < self.height - 1 && edge.get_col() < self.width,
+        }
+    }
+
+    pub fn add_line(&mut self, line: Line) -> Result<()> {
+        let edge = line.get_edge();
+        if !self.is_valid_edge(&edge) {
+            return Err(Error::IllegalMove(edge));
+        }
+
+        if self.is_complete() {
+            return Err(Error::GameOver);
+        }
+
+        let index = self.get_edge_index(&edge);
+        if self.drawn[index] {
+            return Err(Error::LineAlreadyDrawn(edge));
+        }
+
+        self.drawn[index] = true;
         self.lines.push(line);
+        Ok(())
     }
 
     pub fn is_complete(&self) -> bool {
-        self.get_marked_by_player_1().len() + self.get_marked_by_player_2().len() == (self.width - 1.0) as usize * (self.height - 1.0) as usize
-    }
-
-    pub fn update_squares(&mut self, player: Player) {
-        let lines_len = self.lines.len();
-        for i in 0..lines_len {
-            for j in (i + 1)..lines_len {
-                for t in (j + 1)..lines_len {
-                    for k in (t + 1)..lines_len {
-                        let potential_square = Square::new(self.lines[i], self.lines[j], self.lines[t], self.lines[k]);
-
-                        if player == Player::Player2 {
-                            if self.squares.contains(&potential_square) && !self.marked_squares_by_player_1.contains(&potential_square) && 
-                                !self.marked_squares_by_player_2.contains(&potential_square) {
-                                self.marked_squares_by_player_2.push(potential_square);
-                            }
-                        } else if player == Player::Player1 {
-                            if self.squares.contains(&potential_square) && !self.marked_squares_by_player_1.contains(&potential_square) && 
-                                !self.marked_squares_by_player_2.contains(&potential_square) {
-                                self.marked_squares_by_player_1.push(potential_square);
-                            }
-                        }
-                    }
+        self.get_marked_by_player_1().len() + self.get_marked_by_player_2().len() == self.squares.len()
+    }
+
+    // The boxes on either side of the edge, one for edges on the border of the board.
+    pub fn get_adjacent_squares(&self, edge: &Edge) -> Vec<Square> {
+        let mut squares = Vec::with_capacity(2);
+        let (row, col) = (edge.get_row(), edge.get_col());
+
+        match edge.get_orientation() {
+            Orientation::Horizontal => {
+                if row > 0 {
+                    squares.push(Square::new(row - 1, col));
+                }
+                if row < self.height - 1 {
+                    squares.push(Square::new(row, col));
+                }
+            }
+            Orientation::Vertical => {
+                if col > 0 {
+                    squares.push(Square::new(row, col - 1));
+                }
+                if col < self.width - 1 {
+                    squares.push(Square::new(row, col));
                 }
             }
         }
+
+        squares
+    }
+
+    // Only the boxes next to the edge which was just drawn can get completed by it,
+    // so there is no need to look at the rest of the board.
+    pub fn update_squares(&mut self, edge: &Edge, player: Player) -> Vec<Square> {
+        let completed: Vec<Square> = self.get_adjacent_squares(edge).into_iter()
+            .filter(|square| square.get_edges().iter().all(|edge| self.contains_edge(edge)))
+            .collect();
+
+        if player == Player::Player1 {
+            self.marked_squares_by_player_1.extend(completed.iter().cloned());
+        } else if player == Player::Player2 {
+            self.marked_squares_by_player_2.extend(completed.iter().cloned());
+        }
+
+        completed
+    }
+
+    // Draws the edge for the player whose turn it is and passes the turn
+    // to the opponent unless a box got completed.
+    pub fn play(&mut self, edge: Edge) -> Result<MoveOutcome> {
+        let outcome = self.apply(edge)?;
+        self.undone.clear();
+        Ok(outcome)
+    }
+
+    fn apply(&mut self, edge: Edge) -> Result<MoveOutcome> {
+        let player = self.current;
+        self.add_line(Line::new(edge, player))?;
+
+        let outcome = MoveOutcome::new(player, edge, self.update_squares(&edge, player));
+        if !outcome.moves_again() {
+            self.current = player.get_opponent();
+        }
+
+        self.history.push(outcome.clone());
+        Ok(outcome)
+    }
+
+    pub fn get_history(&self) -> &[MoveOutcome] {
+        &self.history
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    // Takes back the last played move together with the boxes it captured
+    // and gives the turn back to the player who made it.
+    pub fn undo(&mut self) -> Option<MoveOutcome> {
+        let outcome = self.history.pop()?;
+        let edge = outcome.get_edge();
+
+        if let Some(position) = self.lines.iter().rposition(|line| line.get_edge() == edge) {
+            self.lines.remove(position);
+        }
+        let index = self.get_edge_index(&edge);
+        self.drawn[index] = false;
+
+        let captured = outcome.get_captured();
+        if outcome.get_player() == Player::Player1 {
+            self.marked_squares_by_player_1.retain(|square| !captured.contains(square));
+        } else if outcome.get_player() == Player::Player2 {
+            self.marked_squares_by_player_2.retain(|square| !captured.contains(square));
+        }
+
+        self.current = outcome.get_player();
+        self.undone.push(outcome.clone());
+        Some(outcome)
+    }
+
+    pub fn redo(&mut self) -> Option<MoveOutcome> {
+        let outcome = self.undone.pop()?;
+        self.current = outcome.get_player();
+        self.apply(outcome.get_edge()).ok()
+    }
+
+    // A single line snapshot of the board which leaves out the move history, e.g.
+    // "3x2|h0,0=1 h1,0=2 v0,0=1 v0,1=2|0,0=2|2" is a board of 3x2 dots with four
+    // lines drawn, the left box taken by player 2 and player 2 to move.
+    pub fn to_position_str(&self) -> String {
+        let lines: Vec<String> = self.lines.iter()
+            .map(|line| format!("{}={}", line.get_edge(), Board::get_player_number(line.get_marked_by())))
+            .collect();
+
+        let mut squares: Vec<(Square, Player)> = self.marked_squares_by_player_1.iter().map(|square| (*square, Player::Player1))
+            .chain(self.marked_squares_by_player_2.iter().map(|square| (*square, Player::Player2)))
+            .collect();
+        squares.sort_by_key(|(square, _)| (square.get_row(), square.get_col()));
+
+        let squares: Vec<String> = squares.iter()
+            .map(|(square, player)| format!("{},{}={}", square.get_row(), square.get_col(), Board::get_player_number(*player)))
+            .collect();
+
+        format!("{}x{}|{}|{}|{}", self.width, self.height, lines.join(" "), squares.join(" "),
+            Board::get_player_number(self.current))
+    }
+
+    pub fn from_position_str(position: &str) -> Result<Board> {
+        let invalid = |message: &str| Error::InvalidPosition(format!("{} in '{}'", message, position));
+
+        let sections: Vec<&str> = position.trim().split('|').map(str::trim).collect();
+        if sections.len() != 4 {
+            return Err(invalid("expected four sections"));
+        }
+
+        let dimensions: Vec<usize> = sections[0].split('x')
+            .map(|value| value.parse().map_err(|_| invalid("malformed size")))
+            .collect::<Result<_>>()?;
+        if dimensions.len() != 2 {
+            return Err(invalid("malformed size"));
+        }
+
+        let mut board = Board::new(dimensions[0], dimensions[1])?;
+
+        for entry in sections[1].split_whitespace() {
+            let (edge, player) = Board::split_owner(entry).ok_or_else(|| invalid("malformed line"))?;
+            board.add_line(Line::new(edge.parse()?, player))?;
+        }
+
+        for entry in sections[2].split_whitespace() {
+            let (coordinates, player) = Board::split_owner(entry).ok_or_else(|| invalid("malformed box"))?;
+            let coordinates: Vec<usize> = coordinates.split(',')
+                .map(|value| value.parse().map_err(|_| invalid("malformed box")))
+                .collect::<Result<_>>()?;
+            if coordinates.len() != 2 {
+                return Err(invalid("malformed box"));
+            }
+
+            let square = Square::new(coordinates[0], coordinates[1]);
+            if !board.squares.contains(&square) || !square.get_edges().iter().all(|edge| board.contains_edge(edge)) {
+                return Err(invalid("box is not complete"));
+            }
+
+            if board.marked_squares_by_player_1.contains(&square) || board.marked_squares_by_player_2.contains(&square) {
+                return Err(invalid("box is taken twice"));
+            }
+
+            if player == Player::Player1 {
+                board.marked_squares_by_player_1.push(square);
+            } else {
+                board.marked_squares_by_player_2.push(square);
+            }
+        }
+
+        let completed = board.squares.iter()
+            .filter(|square| square.get_edges().iter().all(|edge| board.contains_edge(edge)))
+            .count();
+        if completed != board.marked_squares_by_player_1.len() + board.marked_squares_by_player_2.len() {
+            return Err(invalid("completed box without an owner"));
+        }
+
+        board.current = Board::parse_player_number(sections[3]).ok_or_else(|| invalid("malformed side to move"))?;
+        Ok(board)
     }
 
-    pub fn draw(&mut self, ctx: &mut Context, next: Player) -> GameResult {
-        for point in &self.points {
-            let circle = graphics::Mesh::new_circle(
-                ctx,
-                DrawMode::fill(),
-                Point2::new(point.0, point.1),
-                10.0,
-                1.0,
-                graphics::BLACK,
-            )?;
-            graphics::draw(ctx, &circle, (Point2::new(0.0, 0.0),))?;
+    fn get_player_number(player: Player) -> u8 {
+        match player {
+            Player::Player1 => 1,
+            Player::Player2 => 2,
+            Player::Dummy => 0,
         }
+    }
+
+    fn parse_player_number(value: &str) -> Option<Player> {
+        match value {
+            "1" => Some(Player::Player1),
+            "2" => Some(Player::Player2),
+            _ => None,
+        }
+    }
+
+    fn split_owner(entry: &str) -> Option<(&str, Player)> {
+        let mut parts = entry.splitn(2, '=');
+        let value = parts.next()?;
+        let player = Board::parse_player_number(parts.next()?)?;
+        Some((value, player))
+    }
+}
+
+// Maps the logical board onto window pixels and keeps track of the line under the cursor.
+#[derive(Debug, Clone)]
+pub struct BoardView {
+    start_x: f32,
+    step_x: f32,
+    start_y: f32,
+    step_y: f32,
+    temp_line: Option<Edge>,
+    hint_line: Option<Edge>,
+}
+
+impl BoardView {
+    // The boxes are kept square, so a board which is wider than it is tall
+    // gets centered vertically inside the window and vice versa.
+    pub fn new(board: &Board, window_width: f32, window_height: f32, _start_x: f32, _start_y: f32) -> BoardView {
+        let step = ((window_width - 2.0 * _start_x) / board.get_cols() as f32)
+            .min((window_height - 2.0 * _start_y) / board.get_rows() as f32);
+
+        BoardView {
+            start_x: (window_width - step * board.get_cols() as f32) / 2.0,
+            step_x: step,
+            start_y: (window_height - step * board.get_rows() as f32) / 2.0,
+            step_y: step,
+            temp_line: None,
+            hint_line: None,
+        }
+    }
+
+    pub fn get_step_x(&self) -> f32 {
+        self.step_x
+    }
 
-        for line in &self.lines {
-            let origin = Point2::new(line.get_x1(), line.get_y1());
-            let dest = Point2::new(line.get_x2(), line.get_y2());
+    pub fn get_step_y(&self) -> f32 {
+        self.step_y
+    }
 
+    pub fn get_temp_line(&self) -> Option<Edge> {
+        self.temp_line
+    }
+
+    pub fn get_hint_line(&self) -> Option<Edge> {
+        self.hint_line
+    }
+
+    pub fn set_hint_line(&mut self, edge: Option<Edge>) {
+        self.hint_line = edge;
+    }
+
+    pub fn set_temp_line(&mut self, edge: Option<Edge>) {
+        self.temp_line = edge;
+    }
+
+    pub fn get_point(&self, row: usize, col: usize) -> (f32, f32) {
+        (self.start_x + col as f32 * self.step_x, self.start_y + row as f32 * self.step_y)
+    }
+
+    pub fn get_line_points(&self, edge: &Edge) -> ((f32, f32), (f32, f32)) {
+        let end = edge.get_end();
+        (self.get_point(edge.get_row(), edge.get_col()), self.get_point(end.0, end.1))
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, board: &Board, next: Player) -> GameResult {
+        for i in 0..board.get_height() {
+            for j in 0..board.get_width() {
+                let point = self.get_point(i, j);
+                let circle = graphics::Mesh::new_circle(
+                    ctx,
+                    DrawMode::fill(),
+                    Point2::new(point.0, point.1),
+                    10.0,
+                    1.0,
+                    graphics::BLACK,
+                )?;
+                graphics::draw(ctx, &circle, (Point2::new(0.0, 0.0),))?;
+            }
+        }
+
+        for line in board.get_lines() {
             let color = if line.get_marked_by() == Player::Player1 {
                 PLAYER_1_COLOR
             } else {
                 PLAYER_2_COLOR
             };
-            let line = graphics::Mesh::new_line(ctx, &[origin, dest], 5.0, color)?;
-            graphics::draw(ctx, &line, (Point2::new(0.0, 0.0),))?;
+            self.draw_line(ctx, &line.get_edge(), color)?;
         }
 
-        if self.temp_line.get_x1() != 0.0 {
-            let origin = Point2::new(self.temp_line.get_x1(), self.temp_line.get_y1());
-            let dest = Point2::new(self.temp_line.get_x2(), self.temp_line.get_y2());
+        if let Some(edge) = self.hint_line {
+            self.draw_line(ctx, &edge, HINT_COLOR)?;
+        }
 
+        if let Some(edge) = self.temp_line {
             let color = if next == Player::Player1 {
                 PLAYER_1_COLOR
             } else {
                 PLAYER_2_COLOR
             };
-
-            let line = graphics::Mesh::new_line(ctx, &[origin, dest], 5.0, color)?;
-            graphics::draw(ctx, &line, (Point2::new(0.0, 0.0),))?;
+            self.draw_line(ctx, &edge, color)?;
         }
 
         let font = graphics::Font::new(ctx, "/DejaVuSansMono.ttf")?;
-        
-        for square in &self.marked_squares_by_player_1 {
-            let x = square.get_smallest();
-            let distance =  MathOperations::distance(square.get_line1().get_x1(), square.get_line1().get_y1(), square.get_line1().get_x2(), square.get_line1().get_y2());
-            let text = graphics::Text::new((PLAYER_1, font, (7.0 / 10.0) * distance));
-            let w = text.width(ctx);
-            let h = text.height(ctx);                
-            graphics::draw(ctx, &text, DrawParam::default().dest(
-                Point2::new(x.0 + (distance - w as f32) / 2.0, x.1 + (distance - h as f32) / 2.0)))?;
-        }
-
-        for square in &self.marked_squares_by_player_2 {
-            let x = square.get_smallest();
-            let distance =  MathOperations::distance(square.get_line1().get_x1(), square.get_line1().get_y1(), square.get_line1().get_x2(), square.get_line1().get_y2());
-            let text = graphics::Text::new((PLAYER_2, font, (7.0 / 10.0) * distance));
-            let w = text.width(ctx);
-            let h = text.height(ctx);
-            graphics::draw(ctx, &text, DrawParam::default().dest(
-                Point2::new(x.0 + (distance - w as f32) / 2.0, x.1 + (distance - h as f32) / 2.0)))?;
+
+        for square in board.get_marked_by_player_1() {
+            self.draw_square_label(ctx, square, PLAYER_1, font)?;
+        }
+
+        for square in board.get_marked_by_player_2() {
+            self.draw_square_label(ctx, square, PLAYER_2, font)?;
         }
 
         Ok(())
     }
 
-    pub fn update_line(&mut self, next: Player, x: f32, y: f32) {
-        let mut closest_points = Vec::<(f32, f32)>::new();
+    fn draw_line(&self, ctx: &mut Context, edge: &Edge, color: Color) -> GameResult {
+        let points = self.get_line_points(edge);
+        let origin = Point2::new((points.0).0, (points.0).1);
+        let dest = Point2::new((points.1).0, (points.1).1);
 
-        for _i in 0..4 {
-            let mut closest_point: (f32, f32) = (std::f32::MAX, std::f32::MAX);
-            for point in &self.points {
-                let distance = MathOperations::distance(x, y, point.0, point.1);
+        let line = graphics::Mesh::new_line(ctx, &[origin, dest], 5.0, color)?;
+        graphics::draw(ctx, &line, (Point2::new(0.0, 0.0),))
+    }
 
-                if distance < MathOperations::distance(x, y, closest_point.0, closest_point.1) 
-                && !closest_points.contains(&point)
-                && !MathOperations::are_on_same_line(point.0, point.1, &closest_points) {
-                    closest_point = (point.0, point.1);
-                }
-            }
-            
-            if !closest_points.contains(&closest_point) {
-                closest_points.push(closest_point);
-            }
-        }
+    // Shows a short status line above the board.
+    pub fn draw_message(&self, ctx: &mut Context, message: &str) -> GameResult {
+        let font = graphics::Font::new(ctx, "/DejaVuSansMono.ttf")?;
+        let text = graphics::Text::new((message, font, 24.0));
+        let w = text.width(ctx) as f32;
+        let h = text.height(ctx) as f32;
 
-        let mut middle_x = 0.0;
-        let mut middle_y = 0.0;
-        for i in 0..3 {
-            if closest_points[i].0 != closest_points[i + 1].0 {
-                let offset = if closest_points[i].0 < closest_points[i + 1].0 {
-                    closest_points[i].0 
-                } else {
-                    closest_points[i + 1].0 
-                };
-                middle_x = offset + ((closest_points[i].0 - closest_points[i + 1].0) / 2.0).abs();
-            }
+        graphics::draw(ctx, &text, DrawParam::default().dest(
+            Point2::new((WINDOW_WIDTH - w) / 2.0, ((self.start_y - h) / 2.0).max(0.0))))
+    }
 
-            if closest_points[i].1 != closest_points[i + 1].1 {
-                let offset = if closest_points[i].1 < closest_points[i + 1].1 {
-                    closest_points[i].1 
-                } else {
-                    closest_points[i + 1].1 
-                };
-                middle_y = offset + ((closest_points[i].1 - closest_points[i + 1].1) / 2.0).abs();
+    fn draw_square_label(&self, ctx: &mut Context, square: &Square, label: &str, font: Font) -> GameResult {
+        let x = self.get_point(square.get_row(), square.get_col());
+        let text = graphics::Text::new((label, font, (7.0 / 10.0) * self.step_x.min(self.step_y)));
+        let w = text.width(ctx);
+        let h = text.height(ctx);
+        graphics::draw(ctx, &text, DrawParam::default().dest(
+            Point2::new(x.0 + (self.step_x - w as f32) / 2.0, x.1 + (self.step_y - h as f32) / 2.0)))
+    }
+
+    // Picks the side of the box under the cursor which is closest to it.
+    pub fn update_line(&mut self, board: &Board, x: f32, y: f32) {
+        let relative_x = (x - self.start_x) / self.step_x;
+        let relative_y = (y - self.start_y) / self.step_y;
+
+        let col = relative_x.floor().max(0.0).min((board.get_cols() - 1) as f32);
+        let row = relative_y.floor().max(0.0).min((board.get_rows() - 1) as f32);
+
+        let dx = relative_x - col;
+        let dy = relative_y - row;
+        let square = Square::new(row as usize, col as usize);
+        let edges = square.get_edges();
+
+        let distances = [dy * self.step_y, (1.0 - dx) * self.step_x, (1.0 - dy) * self.step_y, dx * self.step_x];
+        let mut closest = 0;
+        for i in 1..4 {
+            if distances[i] < distances[closest] {
+                closest = i;
             }
         }
 
-        if MathOperations::is_inside_triangle(closest_points[0].0, closest_points[0].1, closest_points[3].0, closest_points[3].1, middle_x, middle_y, x, y) {
-            let line = Line::new(closest_points[0].0, closest_points[0].1, closest_points[3].0, closest_points[3].1, next);
-            if !self.lines.contains(&line) {
-                self.temp_line = line;
-            }
-        } else {
-            for i in 0..3 {
-                if MathOperations::is_inside_triangle(closest_points[i].0, closest_points[i].1, closest_points[i + 1].0, closest_points[i + 1].1, middle_x, middle_y, x, y) {
-                    let line = Line::new(closest_points[i].0, closest_points[i].1, closest_points[i + 1].0, closest_points[i + 1].1, next);
-                    if !self.lines.contains(&line) {
-                        self.temp_line = line;
-                        break;
-                    }
-                }
-            }
+        if !board.contains_edge(&edges[closest]) {
+            self.temp_line = Some(edges[closest]);
         }
     }
 }
 
-pub struct MinMax { }
+// How long the computer thinks about a move unless told otherwise.
+pub const DEFAULT_TIME_LIMIT_MILLIS: u64 = 1000;
+
+// With more than one thread every level is searched by a ParallelSearch. The
+// serial search keeps its transposition table from one move to the next, it is
+// only allocated for the first one.
+pub struct MinMax {
+    time_limit: Duration,
+    max_depth: u8,
+    threads: usize,
+    table: Option<TranspositionTable>,
+}
 
 impl MinMax {
 
     pub fn new() -> MinMax {
-        MinMax {
+        MinMax::with_time_limit(Duration::from_millis(DEFAULT_TIME_LIMIT_MILLIS))
+    }
 
+    pub fn with_time_limit(_time_limit: Duration) -> MinMax {
+        MinMax::with_limits(_time_limit, u8::MAX)
+    }
+
+    pub fn with_limits(_time_limit: Duration, _max_depth: u8) -> MinMax {
+        MinMax {
+            time_limit: _time_limit,
+            max_depth: _max_depth,
+            threads: 1,
+            table: None,
         }
     }
 
-    fn get_children(board: &Board, player: Player) -> Vec<Board> {
-        let mut children: Vec<Board> = Vec::new();
-        let start = (X_INITIAL_OFFSET, Y_INITIAL_OFFSET);
+    pub fn get_time_limit(&self) -> Duration {
+        self.time_limit
+    }
 
-        for j in 0..board.get_height() as usize {
-            for i in 0..(board.get_width() - 1.0) as usize {
-                let line = Line::new(
-                start.0 + i as f32 * board.get_step_x(), 
-                start.1 + j as f32 * board.get_step_y(), 
-                start.0 + board.get_step_x() + i as f32 * board.get_step_x(), 
-                start.1 + j as f32 * board.get_step_y(), player);
-                if !board.contains_line(&line) {
-                    let mut cloned = board.clone();
-                    cloned.add_line(line);
-                    cloned.update_squares(player.clone());
-                    children.push(cloned);
-                }
+    pub fn get_max_depth(&self) -> u8 {
+        self.max_depth
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn get_table(&mut self) -> &mut TranspositionTable {
+        self.table.get_or_insert_with(TranspositionTable::default)
+    }
+
+    pub fn alphabeta(board: &Board, max_depth: u8, alpha: i32, beta: i32, is_max: bool) -> (Board, i32) {
+        let mut table = TranspositionTable::for_search(board.get_edges().len() - board.get_lines().len(), max_depth);
+        MinMax::alphabeta_with_table(board, &mut table, max_depth, alpha, beta, is_max)
+    }
+
+    pub fn alphabeta_with_table(board: &Board, table: &mut TranspositionTable, max_depth: u8, alpha: i32, beta: i32,
+            is_max: bool) -> (Board, i32) {
+        MinMax::alphabeta_with_context(board, &mut SearchContext::new(table, None), max_depth, alpha, beta, is_max)
+    }
+
+    pub fn alphabeta_with_context(board: &Board, context: &mut SearchContext, max_depth: u8, alpha: i32, beta: i32,
+            is_max: bool) -> (Board, i32) {
+        let player = if is_max {
+            Player::Player2
+        } else {
+            Player::Player1
+        };
+
+        let mut bitboard = BitBoard::from_board(board);
+        bitboard.set_current_player(player);
+        let (best, value) = MinMax::search(&mut bitboard, context, max_depth, alpha, beta, is_max);
+
+        let mut result = board.clone();
+        if let Some(edge) = best {
+            result.set_current_player(player);
+            if result.play(bitboard.get_edge(edge)).is_err() {
+                return (board.clone(), value);
             }
         }
 
-        for i in 0..(board.get_height() - 1.0) as usize {
-            for j in 0..(board.get_width()) as usize {
-                let line = Line::new(
-                start.0 + j as f32 * board.get_step_x(), 
-                start.1 + i as f32 * board.get_step_y(), 
-                start.0 + j as f32 * board.get_step_x(), 
-                start.1 + board.get_step_y() + i as f32 * board.get_step_y(), 
-                player);
-                if !board.contains_line(&line) {
-                    let mut cloned = board.clone();
-                    cloned.add_line(line);
-                    cloned.update_squares(player.clone());
-                    children.push(cloned);
-                }
+        (result, value)
+    }
+
+    // The edge which leads from the board to the child returned by a search, if any.
+    pub fn get_move(board: &Board, child: &Board) -> Option<Edge> {
+        if child.get_lines().len() > board.get_lines().len() {
+            child.get_history().last().map(|outcome| outcome.get_edge())
+        } else {
+            None
+        }
+    }
+
+    // Searches one level deeper after another until the time is up and returns
+    // the move of the deepest search which finished, together with its value and
+    // depth. The first level is always searched to the end, so there is a move
+    // even if the time limit is tiny.
+    pub fn iterative_deepening(board: &Board, time_limit: Duration) -> (Board, i32, u8) {
+        MinMax::iterative_deepening_with_stop(board, time_limit, u8::MAX, &AtomicBool::new(false))
+    }
+
+    // Like iterative_deepening, but goes no deeper than max_depth and gives up
+    // as soon as the stop flag is set.
+    pub fn iterative_deepening_with_stop(board: &Board, time_limit: Duration, max_depth: u8, stop: &AtomicBool)
+            -> (Board, i32, u8) {
+        MinMax::iterative_deepening_with_table(board, &mut TranspositionTable::default(), time_limit, max_depth, stop)
+    }
+
+    // Like iterative_deepening_with_stop, but fills the given table, which may
+    // already hold what earlier searches found.
+    pub fn iterative_deepening_with_table(board: &Board, table: &mut TranspositionTable, time_limit: Duration,
+            max_depth: u8, stop: &AtomicBool) -> (Board, i32, u8) {
+        let deadline = search::deadline_after(time_limit);
+        let is_max = board.get_current_player() == Player::Player2;
+        let remaining = board.get_edges().len() - board.get_lines().len();
+        let mut result = (board.clone(), 0, 0);
+
+        for depth in 1..=remaining.min(max_depth.max(1) as usize) as u8 {
+            let mut context = SearchContext::with_stop(table, if depth == 1 {
+                    None
+                } else {
+                    deadline
+                }, stop);
+            let (child, value) = MinMax::alphabeta_with_context(board, &mut context, depth, i32::MIN, i32::MAX, is_max);
+
+            if context.is_aborted() {
+                break;
+            }
+
+            result = (child, value, depth);
+
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
             }
         }
 
-        children
+        result
     }
 
-    pub fn alphabeta(board: &Board, max_depth: u8, alpha: i32, beta: i32, is_max: bool) -> (Board, i32) {
-        if board.is_complete() || max_depth <= 0 {
-            return (board.clone(), if is_max {
-                    board.get_marked_by_player_2().len() as i32
+    // Returns the index of the best edge together with its value. Once the
+    // context runs out of time the values are meaningless and nothing gets stored.
+    pub fn search(board: &mut BitBoard, context: &mut SearchContext, max_depth: u8, mut alpha: i32, mut beta: i32,
+            is_max: bool) -> (Option<usize>, i32) {
+        if context.visit() {
+            return (None, 0);
+        }
+
+        if board.is_complete() || max_depth == 0 {
+            return (None, if context.is_margin_evaluation() {
+                    board.get_score(Player::Player2) as i32 - board.get_score(Player::Player1) as i32
+                } else if is_max {
+                    board.get_score(Player::Player2) as i32
                 } else {
-                    -(board.get_marked_by_player_1().len() as i32)
+                    -(board.get_score(Player::Player1) as i32)
                 }
             );
         }
 
-        let mut value: i32;
-        let mut result: (Board, i32) = (board.clone(), 0);
-        let parent_score = (board.get_marked_by_player_1().len(), board.get_marked_by_player_2().len());
+        let hash = board.get_hash();
+        let mut hash_move = None;
+        if let Some(entry) = context.probe(hash) {
+            hash_move = entry.get_best();
+
+            if entry.get_depth() >= max_depth && entry.get_best().is_some() {
+                let value = entry.get_value();
+                let usable = match entry.get_bound() {
+                    Bound::Exact => true,
+                    Bound::Lower => value >= beta,
+                    Bound::Upper => value <= alpha,
+                };
 
-        if is_max {
-            value = std::i32::MIN;
-            let children = MinMax::get_children(board, Player::Player2);
-            let mut all_different: bool = true;
-            for child in &children {
-                if child.get_marked_by_player_2().len() != parent_score.1 {
-                    all_different = false;
+                if usable {
+                    return (entry.get_best(), value);
                 }
             }
+        }
 
-            for child in children {
-                let new_value: (Board, i32);
-                if child.get_marked_by_player_2().len() != parent_score.1 {
-                    new_value = MinMax::alphabeta(&child, max_depth - 1, alpha, beta, true);
-                } else {
-                    new_value = MinMax::alphabeta(&child, max_depth - 1, alpha, beta, false);
+        let (original_alpha, original_beta) = (alpha, beta);
+        let mut value: i32;
+        let mut result: (Option<usize>, i32) = (None, 0);
+        let ply = board.get_depth();
+        let children = MinMax::order_moves(board, context, hash_move);
+
+        if is_max {
+            value = i32::MIN;
+
+            for edge in children {
+                let quiet = board.get_captures(edge) == 0;
+                board.make_move(edge);
+                let is_max = board.get_current_player() == Player::Player2;
+                let new_value = MinMax::search(board, context, max_depth - 1, alpha, beta, is_max);
+                board.unmake_move();
+
+                if context.is_aborted() {
+                    return (None, 0);
                 }
 
                 if value < new_value.1 {
-                    result = (child, new_value.1);
+                    result = (Some(edge), new_value.1);
                     value = new_value.1;
                 }
 
-                let alpha = if alpha < value {
-                    value 
-                } else {
-                    alpha
-                };
+                alpha = alpha.max(value);
 
-                if alpha >= beta && all_different {
+                if alpha >= beta {
+                    if quiet {
+                        context.add_cutoff(ply, edge, max_depth);
+                    }
                     break;
                 }
             }
         } else {
-            value = std::i32::MAX;
-            let children = MinMax::get_children(board, Player::Player1);
-            let mut all_different: bool = true;
+            value = i32::MAX;
 
-            for child in &children{
-                if child.get_marked_by_player_1().len() != parent_score.0 {
-                    all_different = false;
-                }
-            }
+            for edge in children {
+                let quiet = board.get_captures(edge) == 0;
+                board.make_move(edge);
+                let is_max = board.get_current_player() == Player::Player2;
+                let new_value = MinMax::search(board, context, max_depth - 1, alpha, beta, is_max);
+                board.unmake_move();
 
-            for child in children {
-                let new_value: (Board, i32);
-                if child.get_marked_by_player_1().len() != parent_score.0 {
-                    new_value = MinMax::alphabeta(&child, max_depth - 1, alpha, beta, true);
-                } else {
-                    new_value = MinMax::alphabeta(&child, max_depth - 1, alpha, beta, false);
+                if context.is_aborted() {
+                    return (None, 0);
                 }
 
                 if value > new_value.1 {
-                    result = (child, new_value.1);
+                    result = (Some(edge), new_value.1);
                     value = new_value.1;
                 }
 
-                let beta = if beta > value {
-                    value 
-                } else {
-                    beta
-                };
-                
-                if alpha >= beta && all_different {
+                beta = beta.min(value);
+
+                if alpha >= beta {
+                    if quiet {
+                        context.add_cutoff(ply, edge, max_depth);
+                    }
                     break;
                 }
             }
         }
 
+        let bound = if value <= original_alpha {
+            Bound::Upper
+        } else if value >= original_beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        context.store(Entry::new(hash, max_depth, value, bound, result.0));
+
         result
     }
+
+    // The move the table remembers goes first, then the captures, then the moves
+    // which leave no box with three sides drawn and last the sacrifices. Within
+    // each group the killers of the ply come first and the rest by their history.
+    pub fn order_moves(board: &BitBoard, context: &SearchContext, hash_move: Option<usize>) -> Vec<usize> {
+        let mut children = board.get_free_edges();
+        if !context.is_move_ordering() {
+            return children;
+        }
+
+        let killers = context.get_killers(board.get_depth());
+        children.sort_by_cached_key(|edge| {
+            let group = if hash_move == Some(*edge) {
+                0
+            } else if board.get_captures(*edge) > 0 {
+                1
+            } else if board.get_adjacent_squares(*edge).iter().all(|square| board.get_drawn_sides(*square) < 2) {
+                2
+            } else {
+                3
+            };
+            let killer = killers.iter().position(|killer| *killer == Some(*edge)).unwrap_or(killers.len());
+
+            (group, killer, Reverse(context.get_history(*edge)))
+        });
+
+        children
+    }
+}
+
+impl Default for MinMax {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub struct LabelButton {
@@ -621,20 +1147,29 @@ impl LabelButton {
     }
 }
 
+const ENGINE_FONT_SIZE: f32 = 28.0;
+
 pub struct MainMenu {
     one_player_entry: LabelButton,
     two_player_entry: LabelButton,
+    computers_entry: LabelButton,
+    difficulty_entry: LabelButton,
+    engine_entry: LabelButton,
+    player_1_engine_entry: LabelButton,
+    stats_entry: LabelButton,
 }
 
 impl MainMenu {
-    pub fn new(ctx: &mut Context) -> GameResult<MainMenu> {
+    pub fn new(ctx: &mut Context) -> Result<MainMenu> {
         let font = Font::new(ctx, "/DejaVuSansMono.ttf")?;
         let font_size = 40.0;
-        let start_y = WINDOW_HEIGHT / 3.0;
-        let step = WINDOW_HEIGHT / 5.0;
+        let start_y = WINDOW_HEIGHT / 4.0;
+        let step = WINDOW_HEIGHT / 6.0;
+        let small_step = ENGINE_FONT_SIZE * 1.25;
 
         let text_one_player = Text::new(("1 Player", font, font_size));
         let text_two_player = Text::new(("2 Players", font, font_size));
+        let text_computers = Text::new(("CPU vs CPU", font, font_size));
 
         let text_one_player_width = text_one_player.width(ctx) as f32;
         let text_one_player_height = text_one_player.height(ctx) as f32;
@@ -642,17 +1177,60 @@ impl MainMenu {
         let text_two_player_width = text_two_player.width(ctx) as f32;
         let text_two_player_height = text_two_player.height(ctx) as f32;
 
+        let text_computers_width = text_computers.width(ctx) as f32;
+        let text_computers_height = text_computers.height(ctx) as f32;
+
         let x = (WINDOW_WIDTH - text_one_player_width) / 2.0;
 
+        // the label changes with the chosen engine, so the widest one is clickable
+        let mut text_engine_width: f32 = 0.0;
+        let mut text_engine_height: f32 = 0.0;
+        for kind in EngineKind::ALL.iter() {
+            let text_engine = Text::new((MainMenu::get_engine_label(Player::Player2, *kind), font, ENGINE_FONT_SIZE));
+            text_engine_width = text_engine_width.max(text_engine.width(ctx) as f32);
+            text_engine_height = text_engine_height.max(text_engine.height(ctx) as f32);
+        }
+
+        let text_stats = Text::new(("Ratings", font, ENGINE_FONT_SIZE));
+        let text_stats_width = text_stats.width(ctx) as f32;
+        let text_stats_height = text_stats.height(ctx) as f32;
+
+        let mut text_difficulty_width: f32 = 0.0;
+        let mut text_difficulty_height: f32 = 0.0;
+        for difficulty in Difficulty::ALL.iter() {
+            let text_difficulty = Text::new((MainMenu::get_difficulty_label(*difficulty), font, ENGINE_FONT_SIZE));
+            text_difficulty_width = text_difficulty_width.max(text_difficulty.width(ctx) as f32);
+            text_difficulty_height = text_difficulty_height.max(text_difficulty.height(ctx) as f32);
+        }
+
         let k = MainMenu {
             one_player_entry: LabelButton::new(x, start_y, text_one_player_width, text_one_player_height, String::from("1 Player")),
             two_player_entry: LabelButton::new(x, start_y + step, text_two_player_width, text_two_player_height, String::from("2 Players")),
+            computers_entry: LabelButton::new(x, start_y + 2.0 * step, text_computers_width, text_computers_height, String::from("CPU vs CPU")),
+            difficulty_entry: LabelButton::new((WINDOW_WIDTH - text_difficulty_width) / 2.0, start_y + 3.0 * step,
+                text_difficulty_width, text_difficulty_height, String::new()),
+            engine_entry: LabelButton::new((WINDOW_WIDTH - text_engine_width) / 2.0, start_y + 3.0 * step + small_step,
+                text_engine_width, text_engine_height, String::new()),
+            player_1_engine_entry: LabelButton::new((WINDOW_WIDTH - text_engine_width) / 2.0, start_y + 3.0 * step + 2.0 * small_step,
+                text_engine_width, text_engine_height, String::new()),
+            stats_entry: LabelButton::new((WINDOW_WIDTH - text_stats_width) / 2.0, start_y + 3.0 * step + 3.0 * small_step,
+                text_stats_width, text_stats_height, String::from("Ratings")),
         };
 
         Ok(k)
     }
 
-    pub fn draw(&mut self, ctx: &mut Context) -> GameResult {
+    fn get_difficulty_label(difficulty: Difficulty) -> String {
+        format!("Level: {}", difficulty)
+    }
+
+    // player 1 only has an engine when the computers play each other
+    fn get_engine_label(player: Player, engine: EngineKind) -> String {
+        format!("Computer {}: {}", Board::get_player_number(player), engine)
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, settings: &DifficultySettings, player_1_settings: &DifficultySettings)
+            -> GameResult {
         let font = Font::new(ctx, "/DejaVuSansMono.ttf")?;
         let font_size = 40.0;
 
@@ -668,6 +1246,29 @@ impl MainMenu {
         let text = Text::new((self.two_player_entry.get_text(), font, font_size));
         graphics::draw(ctx, &text, DrawParam::default()
         .dest(Point2::new(self.two_player_entry.get_x(), self.two_player_entry.get_y())))?;
+
+        let text = Text::new((self.computers_entry.get_text(), font, font_size));
+        graphics::draw(ctx, &text, DrawParam::default()
+        .dest(Point2::new(self.computers_entry.get_x(), self.computers_entry.get_y())))?;
+
+        let text = Text::new((MainMenu::get_difficulty_label(settings.get_difficulty()), font, ENGINE_FONT_SIZE));
+        let text_width = text.width(ctx) as f32;
+        graphics::draw(ctx, &text, DrawParam::default()
+        .dest(Point2::new((WINDOW_WIDTH - text_width) / 2.0, self.difficulty_entry.get_y())))?;
+
+        let text = Text::new((MainMenu::get_engine_label(Player::Player2, settings.get_engine()), font, ENGINE_FONT_SIZE));
+        let text_width = text.width(ctx) as f32;
+        graphics::draw(ctx, &text, DrawParam::default()
+        .dest(Point2::new((WINDOW_WIDTH - text_width) / 2.0, self.engine_entry.get_y())))?;
+
+        let text = Text::new((MainMenu::get_engine_label(Player::Player1, player_1_settings.get_engine()), font, ENGINE_FONT_SIZE));
+        let text_width = text.width(ctx) as f32;
+        graphics::draw(ctx, &text, DrawParam::default()
+        .dest(Point2::new((WINDOW_WIDTH - text_width) / 2.0, self.player_1_engine_entry.get_y())))?;
+
+        let text = Text::new((self.stats_entry.get_text(), font, ENGINE_FONT_SIZE));
+        graphics::draw(ctx, &text, DrawParam::default()
+        .dest(Point2::new(self.stats_entry.get_x(), self.stats_entry.get_y())))?;
         
         Ok(())
     }
@@ -681,14 +1282,107 @@ impl MainMenu {
         MathOperations::is_inside_rectangle(x, y, self.two_player_entry.get_x(), self.two_player_entry.get_y(), 
             self.two_player_entry.get_width(), self.two_player_entry.get_height())
     }
+
+    pub fn is_on_computers_entry(&self, x: f32, y: f32) -> bool {
+        MathOperations::is_inside_rectangle(x, y, self.computers_entry.get_x(), self.computers_entry.get_y(),
+            self.computers_entry.get_width(), self.computers_entry.get_height())
+    }
+
+    pub fn is_on_difficulty_entry(&self, x: f32, y: f32) -> bool {
+        MathOperations::is_inside_rectangle(x, y, self.difficulty_entry.get_x(), self.difficulty_entry.get_y(),
+            self.difficulty_entry.get_width(), self.difficulty_entry.get_height())
+    }
+
+    pub fn is_on_engine_entry(&self, x: f32, y: f32) -> bool {
+        MathOperations::is_inside_rectangle(x, y, self.engine_entry.get_x(), self.engine_entry.get_y(),
+            self.engine_entry.get_width(), self.engine_entry.get_height())
+    }
+
+    pub fn is_on_player_1_engine_entry(&self, x: f32, y: f32) -> bool {
+        MathOperations::is_inside_rectangle(x, y, self.player_1_engine_entry.get_x(), self.player_1_engine_entry.get_y(),
+            self.player_1_engine_entry.get_width(), self.player_1_engine_entry.get_height())
+    }
+
+    pub fn is_on_stats_entry(&self, x: f32, y: f32) -> bool {
+        MathOperations::is_inside_rectangle(x, y, self.stats_entry.get_x(), self.stats_entry.get_y(),
+            self.stats_entry.get_width(), self.stats_entry.get_height())
+    }
+}
+
+// How many of the best rated players fit on the stats screen.
+const STATS_LINES: usize = 10;
+
+pub struct StatsScreen {
+    back: LabelButton,
+}
+
+impl StatsScreen {
+    pub fn new(ctx: &mut Context) -> Result<StatsScreen> {
+        let font = Font::new(ctx, "/DejaVuSansMono.ttf")?;
+
+        let text_back = Text::new(("Back", font, ENGINE_FONT_SIZE));
+        let text_back_width = text_back.width(ctx) as f32;
+        let text_back_height = text_back.height(ctx) as f32;
+
+        let k = StatsScreen {
+            back: LabelButton::new((WINDOW_WIDTH - text_back_width) / 2.0, WINDOW_HEIGHT - text_back_height - 30.0,
+                text_back_width, text_back_height, String::from("Back")),
+        };
+
+        Ok(k)
+    }
+
+    // Lists the best rated players with their records and the level suggested for the profile.
+    pub fn draw(&mut self, ctx: &mut Context, ratings: &Ratings, profile: &str) -> GameResult {
+        let font = Font::new(ctx, "/DejaVuSansMono.ttf")?;
+        let font_size = 40.0;
+        let line_size = 20.0;
+
+        let text = Text::new(("Ratings", font, font_size));
+        let text_width = text.width(ctx) as f32;
+        graphics::draw(ctx, &text, DrawParam::default()
+        .dest(Point2::new((WINDOW_WIDTH - text_width) / 2.0, 30.0)))?;
+
+        let mut y = 100.0;
+        let players = ratings.get_players();
+        if players.is_empty() {
+            let text = Text::new(("No rated games yet", font, line_size));
+            graphics::draw(ctx, &text, DrawParam::default().dest(Point2::new(X_INITIAL_OFFSET, y)))?;
+            y += line_size * 1.5;
+        }
+
+        for (name, rating) in players.into_iter().take(STATS_LINES) {
+            let line = format!("{:<20} {:>6.0}  {}-{}-{}", name, rating.get_value(), rating.get_wins(),
+                rating.get_draws(), rating.get_losses());
+            let text = Text::new((line, font, line_size));
+            graphics::draw(ctx, &text, DrawParam::default().dest(Point2::new(X_INITIAL_OFFSET, y)))?;
+            y += line_size * 1.5;
+        }
+
+        let suggestion = format!("Suggested level for {}: {}", profile, ratings.suggest_difficulty(profile));
+        let text = Text::new((suggestion, font, line_size));
+        graphics::draw(ctx, &text, DrawParam::default().dest(Point2::new(X_INITIAL_OFFSET, y + line_size)))?;
+
+        let text = Text::new((self.back.get_text(), font, ENGINE_FONT_SIZE));
+        graphics::draw(ctx, &text, DrawParam::default()
+        .dest(Point2::new(self.back.get_x(), self.back.get_y())))?;
+
+        Ok(())
+    }
+
+    pub fn is_on_back(&self, x: f32, y: f32) -> bool {
+        MathOperations::is_inside_rectangle(x, y, self.back.get_x(), self.back.get_y(),
+            self.back.get_width(), self.back.get_height())
+    }
 }
 
 pub struct EndMenu {
     restart: LabelButton,
+    review: LabelButton,
 }
 
 impl EndMenu {
-    pub fn new(ctx: &mut Context) -> GameResult<EndMenu> {
+    pub fn new(ctx: &mut Context) -> Result<EndMenu> {
         let font = Font::new(ctx, "/DejaVuSansMono.ttf")?;
         let font_size = 40.0;
 
@@ -699,8 +1393,16 @@ impl EndMenu {
         let x = (WINDOW_WIDTH - text_restart_width) / 2.0;
         let y = WINDOW_HEIGHT - text_restart_height - 50.0;
 
+        let text_review = Text::new(("Review", font, font_size));
+        let text_review_width = text_review.width(ctx) as f32;
+        let text_review_height = text_review.height(ctx) as f32;
+
+        let review_x = (WINDOW_WIDTH - text_review_width) / 2.0;
+        let review_y = y - text_review_height - 20.0;
+
         let k = EndMenu {
             restart: LabelButton::new(x, y, text_restart_width, text_restart_height, String::from("Play again!")),
+            review: LabelButton::new(review_x, review_y, text_review_width, text_review_height, String::from("Review")),
         };
 
         Ok(k)
@@ -737,6 +1439,10 @@ impl EndMenu {
         let text = Text::new((self.restart.get_text(), font, font_size));
         graphics::draw(ctx, &text, DrawParam::default()
         .dest(Point2::new(self.restart.get_x(), self.restart.get_y())))?;
+
+        let text = Text::new((self.review.get_text(), font, font_size));
+        graphics::draw(ctx, &text, DrawParam::default()
+        .dest(Point2::new(self.review.get_x(), self.review.get_y())))?;
         
         Ok(())
     }
@@ -745,5 +1451,10 @@ impl EndMenu {
         MathOperations::is_inside_rectangle(x, y, self.restart.get_x(), self.restart.get_y(), 
                 self.restart.get_width(), self.restart.get_height())
     }
+
+    pub fn is_on_review(&self, x: f32, y: f32) -> bool {
+        MathOperations::is_inside_rectangle(x, y, self.review.get_x(), self.review.get_y(),
+                self.review.get_width(), self.review.get_height())
+    }
 }
 
@@ -0,0 +1,65 @@
+use std::fmt;
+use std::io;
+
+use ggez::GameError;
+
+use crate::entities::Edge;
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidDimensions(usize, usize),
+    IllegalMove(Edge),
+    LineAlreadyDrawn(Edge),
+    GameOver,
+    AssetLoad(String),
+    InvalidNotation(String),
+    InvalidRecord(String),
+    InvalidRatings(String),
+    InvalidPosition(String),
+    UnknownEngine(String),
+    UnknownDifficulty(String),
+    InvalidEngineConfig(String),
+    TooManyEdges(usize, usize),
+    Stopped,
+    Io(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidDimensions(width, height) =>
+                write!(f, "Width and height must be greater than 1, got {}x{}", width, height),
+            Error::IllegalMove(edge) => write!(f, "The line {} is not on the board", edge),
+            Error::LineAlreadyDrawn(edge) => write!(f, "The line {} is already drawn", edge),
+            Error::GameOver => write!(f, "The game is already over"),
+            Error::AssetLoad(message) => write!(f, "Failed to load asset: {}", message),
+            Error::InvalidNotation(notation) => write!(f, "'{}' is not a valid line", notation),
+            Error::InvalidRecord(message) => write!(f, "Invalid game record: {}", message),
+            Error::InvalidRatings(message) => write!(f, "Invalid ratings file: {}", message),
+            Error::InvalidPosition(message) => write!(f, "Invalid position: {}", message),
+            Error::UnknownEngine(name) => write!(f, "There is no engine called '{}'", name),
+            Error::UnknownDifficulty(name) => write!(f, "There is no difficulty called '{}'", name),
+            Error::InvalidEngineConfig(config) => write!(f, "'{}' is not a valid engine configuration", config),
+            Error::TooManyEdges(remaining, max) =>
+                write!(f, "The position has {} free lines, the solver handles at most {}", remaining, max),
+            Error::Stopped => write!(f, "The search was stopped before it finished"),
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<GameError> for Error {
+    fn from(error: GameError) -> Self {
+        Error::AssetLoad(error.to_string())
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
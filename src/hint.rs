@@ -0,0 +1,75 @@
+use std::fmt;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use crate::analysis::Analysis;
+use crate::entities::{Board, Edge};
+use crate::error::{Error, Result};
+
+// How good the suggested move is for the player who asked.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Evaluation {
+    // The final margin under perfect play, as worked out by the solver.
+    Exact(i32),
+    // The box margin a MinMax search of the given depth expects after the moves
+    // it looked at, which says nothing about the boxes still free.
+    Estimate { value: i32, depth: u8 },
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Evaluation::Exact(margin) if *margin > 0 => write!(f, "wins by {}", margin),
+            Evaluation::Exact(margin) if *margin < 0 => write!(f, "loses by {}", -margin),
+            Evaluation::Exact(_) => write!(f, "draws"),
+            Evaluation::Estimate { value, depth } => write!(f, "estimated margin {:+} at depth {}", value, depth),
+        }
+    }
+}
+
+// A suggested move for the player to move. Late in the game the solver finds
+// the best move, before that MinMax searches for as long as it is allowed to.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Hint {
+    edge: Edge,
+    evaluation: Evaluation,
+}
+
+impl Hint {
+    pub fn new(_edge: Edge, _evaluation: Evaluation) -> Hint {
+        Hint {
+            edge: _edge,
+            evaluation: _evaluation,
+        }
+    }
+
+    pub fn find(board: &Board, time_limit: Duration, stop: &AtomicBool) -> Result<Hint> {
+        Hint::from_analysis(&Analysis::run_with_stop(board, time_limit, u8::MAX, stop)?)
+    }
+
+    pub fn from_analysis(analysis: &Analysis) -> Result<Hint> {
+        let best = analysis.get_best().ok_or(Error::GameOver)?;
+
+        let evaluation = if analysis.is_exact() {
+            Evaluation::Exact(best.get_value())
+        } else {
+            Evaluation::Estimate { value: best.get_value(), depth: analysis.get_depth() }
+        };
+
+        Ok(Hint::new(best.get_edge(), evaluation))
+    }
+
+    pub fn get_edge(&self) -> Edge {
+        self.edge
+    }
+
+    pub fn get_evaluation(&self) -> Evaluation {
+        self.evaluation
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.edge, self.evaluation)
+    }
+}
@@ -1 +1,17 @@
-pub mod entities;
\ No newline at end of file
+pub mod analysis;
+pub mod bitboard;
+pub mod chains;
+pub mod difficulty;
+pub mod engine;
+pub mod entities;
+pub mod error;
+pub mod hint;
+pub mod mcts;
+pub mod parallel;
+pub mod ratings;
+pub mod record;
+pub mod search;
+pub mod solver;
+pub mod tournament;
+pub mod transposition;
+pub mod worker;
@@ -1,42 +1,367 @@
-use ggez;
-
 use ggez::event::{KeyCode, KeyMods};
 use ggez::event::{self, MouseButton};
 use ggez::conf::{WindowMode, WindowSetup};
 use ggez::graphics::{self};
-use ggez::{Context, GameResult, ContextBuilder, timer};
+use ggez::{Context, GameResult, ContextBuilder};
+use std::env;
 use std::time::{Duration, Instant};
 
-use dotsnboxes::entities::{State, Player, Board, MinMax, MainMenu, 
-    EndMenu, WINDOW_WIDTH, WINDOW_HEIGHT, WIDTH, HEIGHT, X_INITIAL_OFFSET, Y_INITIAL_OFFSET};
+use dotsnboxes::analysis::Analysis;
+use dotsnboxes::difficulty::DifficultySettings;
+use dotsnboxes::engine::Engine;
+use dotsnboxes::error::Result;
+use dotsnboxes::hint::Hint;
+use dotsnboxes::ratings::Ratings;
+use dotsnboxes::record::GameRecord;
+use dotsnboxes::worker::{EngineMove, SearchWorker};
+
+use dotsnboxes::entities::{State, Player, Board, Edge, BoardView, MainMenu,
+    EndMenu, StatsScreen, PLAYER_1, WINDOW_WIDTH, WINDOW_HEIGHT, WIDTH, HEIGHT, X_INITIAL_OFFSET, Y_INITIAL_OFFSET};
 
 const UPDATES_PER_SECOND: f32 = 10.0;
 const MILLIS_PER_UPDATE: u64 = (1.0 / UPDATES_PER_SECOND * 1000.0) as u64;
 
+const SAVE_FILE: &str = "dotsnboxes.save";
+const RATINGS_FILE: &str = "dotsnboxes.ratings";
+
+// the name the human is rated under in one player games
+const PROFILE_VARIABLE: &str = "DOTSNBOXES_PROFILE";
+
+const HINT_TIME_MILLIS: u64 = 1000;
+const REVIEW_TIME_MILLIS: u64 = 500;
+
+// how long the computers wait between their moves when they play each other
+const MOVE_DELAY_MILLIS: u64 = 500;
+const MOVE_DELAY_STEP_MILLIS: u64 = 100;
+const MAX_MOVE_DELAY_MILLIS: u64 = 5000;
+
 struct GameState {
     board: Board,
+    view: BoardView,
     last_update: Instant,
     main_menu: MainMenu,
     end_menu: EndMenu,
+    stats_screen: StatsScreen,
     mode: State,
-    next: Player,
+    worker: Option<SearchWorker<EngineMove>>,
+    settings: DifficultySettings,
+    // only used when the computers play each other
+    player_1_settings: DifficultySettings,
+    // the computers' engines are kept from one move to the next for as long as
+    // the game and their settings stay the same
+    engine: Option<(DifficultySettings, Box<dyn Engine>)>,
+    player_1_engine: Option<(DifficultySettings, Box<dyn Engine>)>,
+    move_delay: Duration,
+    last_move: Instant,
+    paused: bool,
+    // analyses belong to the position they were asked for and are dropped once it changes
+    analysis_worker: Option<(String, SearchWorker<Result<Analysis>>)>,
+    analysis: Option<(String, Analysis)>,
+    // the moves of the finished game while it is reviewed
+    review: Vec<Edge>,
+    ratings: Ratings,
+    profile: String,
 }
 
 impl GameState {
-    pub fn new(ctx: &mut Context) -> Self {
-        GameState {
-            board: Board::new(WIDTH, HEIGHT, WINDOW_WIDTH, WINDOW_HEIGHT, X_INITIAL_OFFSET, Y_INITIAL_OFFSET),
+    pub fn new(ctx: &mut Context) -> Result<Self> {
+        let board = Board::new(WIDTH, HEIGHT)?;
+        let ratings = Ratings::load_or_new(RATINGS_FILE).unwrap_or_else(|e| {
+            println!("Could not load the ratings: {}", e);
+            Ratings::new()
+        });
+        let profile = env::var(PROFILE_VARIABLE).unwrap_or_else(|_| String::from(PLAYER_1));
+
+        Ok(GameState {
+            view: BoardView::new(&board, WINDOW_WIDTH, WINDOW_HEIGHT, X_INITIAL_OFFSET, Y_INITIAL_OFFSET),
+            board,
             last_update: Instant::now(),
-            main_menu: MainMenu::new(ctx).unwrap(),
-            end_menu: EndMenu::new(ctx).unwrap(),
+            main_menu: MainMenu::new(ctx)?,
+            end_menu: EndMenu::new(ctx)?,
+            stats_screen: StatsScreen::new(ctx)?,
             mode: State::None,
-            next: Player::Player1,
+            worker: None,
+            // the player starts at the level which matches their rating
+            settings: ratings.suggest_difficulty(&profile).get_settings(),
+            player_1_settings: DifficultySettings::default(),
+            engine: None,
+            player_1_engine: None,
+            move_delay: Duration::from_millis(MOVE_DELAY_MILLIS),
+            last_move: Instant::now(),
+            paused: false,
+            analysis_worker: None,
+            analysis: None,
+            review: Vec::new(),
+            ratings,
+            profile,
+        })
+    }
+
+    fn get_computer_settings(&self, player: Player) -> Option<&DifficultySettings> {
+        match (&self.mode, player) {
+            (State::OnePlayer, Player::Player2) | (State::ComputerVsComputer, Player::Player2) => Some(&self.settings),
+            (State::ComputerVsComputer, Player::Player1) => Some(&self.player_1_settings),
+            _ => None,
+        }
+    }
+
+    fn start_computer_turn(&mut self) {
+        if self.worker.is_none() && !self.board.is_complete() {
+            if let Some(engine) = self.take_engine(self.board.get_current_player()) {
+                self.worker = Some(SearchWorker::start_keeping_engine(self.board.clone(), engine));
+            }
+        }
+    }
+
+    // The engine of the computer playing the given player, a new one if its
+    // settings changed since its last move.
+    fn take_engine(&mut self, player: Player) -> Option<Box<dyn Engine>> {
+        let settings = self.get_computer_settings(player)?.clone();
+        let kept = if player == Player::Player1 {
+            self.player_1_engine.take()
+        } else {
+            self.engine.take()
+        };
+
+        Some(match kept {
+            Some((kept_settings, engine)) if kept_settings == settings => engine,
+            _ => settings.create_engine(),
+        })
+    }
+
+    fn keep_engine(&mut self, player: Player, engine: Box<dyn Engine>) {
+        if let Some(settings) = self.get_computer_settings(player).cloned() {
+            if player == Player::Player1 {
+                self.player_1_engine = Some((settings, engine));
+            } else {
+                self.engine = Some((settings, engine));
+            }
+        }
+    }
+
+    // A new board gets new engines, nothing they remember is of use there.
+    fn forget_engines(&mut self) {
+        self.engine = None;
+        self.player_1_engine = None;
+    }
+
+    fn change_move_delay(&mut self, longer: bool) {
+        let millis = self.move_delay.as_millis() as u64;
+        let millis = if longer {
+            (millis + MOVE_DELAY_STEP_MILLIS).min(MAX_MOVE_DELAY_MILLIS)
+        } else {
+            millis.saturating_sub(MOVE_DELAY_STEP_MILLIS)
+        };
+        self.move_delay = Duration::from_millis(millis);
+    }
+
+    // e.g. "greedy vs minmax, 500 ms a move, paused"
+    fn get_computers_message(&self) -> String {
+        format!("{} vs {}, {} ms a move{}", self.player_1_settings.get_engine(), self.settings.get_engine(),
+            self.move_delay.as_millis(), if self.paused {
+                ", paused"
+            } else {
+                ""
+            })
+    }
+
+    fn stop_computer_turn(&mut self) {
+        if let Some(mut worker) = self.worker.take() {
+            worker.cancel();
+        }
+    }
+
+    fn is_human_turn(&self) -> bool {
+        (self.mode == State::OnePlayer && self.board.get_current_player() == Player::Player1 && self.worker.is_none()) ||
+            self.mode == State::TwoPlayers
+    }
+
+    fn start_analysis(&mut self, time_limit: u64) {
+        if self.analysis_worker.is_none() && self.analysis.is_none() && !self.board.is_complete() {
+            let board = self.board.clone();
+            let worker = SearchWorker::spawn(move |stop|
+                Analysis::run_with_stop(&board, Duration::from_millis(time_limit), u8::MAX, stop));
+            self.analysis_worker = Some((self.board.to_position_str(), worker));
+        }
+    }
+
+    fn start_hint(&mut self) {
+        if self.is_human_turn() {
+            self.start_analysis(HINT_TIME_MILLIS);
+        }
+    }
+
+    fn update_analysis(&mut self) {
+        let position = self.board.to_position_str();
+
+        if let Some((analysis_position, worker)) = self.analysis_worker.as_mut() {
+            if *analysis_position != position {
+                self.analysis_worker = None;
+            } else if let Some(result) = worker.poll() {
+                match result {
+                    Ok(analysis) => self.analysis = Some((position.clone(), analysis)),
+                    Err(e) => println!("Could not analyse the position: {}", e),
+                }
+                self.analysis_worker = None;
+            }
+        }
+
+        if self.analysis.as_ref().is_some_and(|(analysis_position, _)| *analysis_position != position) {
+            self.analysis = None;
+        }
+
+        // every position of a reviewed game is analysed without asking
+        if self.mode == State::Review {
+            self.start_analysis(REVIEW_TIME_MILLIS);
+        }
+
+        self.view.set_hint_line(self.analysis.as_ref()
+            .and_then(|(_, analysis)| analysis.get_best())
+            .map(|best| best.get_edge()));
+    }
+
+    // A finished one player game rates the profile against the computer's level.
+    fn end_game(&mut self) {
+        if self.mode == State::OnePlayer {
+            let level = Ratings::get_level_name(self.settings.get_difficulty());
+            self.ratings.add_game(&self.profile, &level, self.board.get_marked_by_player_1().len(),
+                self.board.get_marked_by_player_2().len());
+
+            if let Err(e) = self.ratings.save(RATINGS_FILE) {
+                println!("Could not save the ratings: {}", e);
+            }
+        }
+
+        self.mode = State::GameOver;
+    }
+
+    fn start_review(&mut self) {
+        self.stop_computer_turn();
+        self.review = self.board.get_history().iter().map(|outcome| outcome.get_edge()).collect();
+        while self.board.undo().is_some() {}
+        self.mode = State::Review;
+    }
+
+    fn stop_review(&mut self) {
+        while self.board.redo().is_some() {}
+        self.review.clear();
+        self.mode = State::GameOver;
+    }
+
+    // e.g. "Move 3/12: v0,1 -2, best h1,0 0"
+    fn get_review_message(&self) -> String {
+        let step = self.board.get_history().len();
+        let played = match self.review.get(step) {
+            Some(edge) => *edge,
+            None => return format!("Move {}/{}: game over", step, self.review.len()),
+        };
+
+        match self.analysis.as_ref().map(|(_, analysis)| analysis) {
+            Some(analysis) => {
+                let value = |edge: Edge| analysis.get_move(edge).map_or(String::from("?"), |found| found.get_value().to_string());
+                let best = analysis.get_best().map_or(played, |best| best.get_edge());
+                format!("Move {}/{}: {} {}, best {} {}", step + 1, self.review.len(), played, value(played), best, value(best))
+            }
+            None => format!("Move {}/{}: {}, analysing...", step + 1, self.review.len(), played),
+        }
+    }
+
+    fn undo(&mut self) {
+        self.stop_computer_turn();
+
+        if self.mode == State::OnePlayer {
+            // the computer's replies are taken back as well, so it is the human's turn again
+            while let Some(outcome) = self.board.undo() {
+                if outcome.get_player() == Player::Player1 {
+                    break;
+                }
+            }
+        } else if self.mode == State::TwoPlayers || self.mode == State::Review {
+            self.board.undo();
+        }
+    }
+
+    fn redo(&mut self) {
+        self.stop_computer_turn();
+
+        if self.mode == State::OnePlayer {
+            if self.board.redo().is_some() {
+                while self.board.get_current_player() == Player::Player2 {
+                    if self.board.redo().is_none() {
+                        break;
+                    }
+                }
+            }
+            self.start_computer_turn();
+        } else if self.mode == State::TwoPlayers || self.mode == State::Review {
+            self.board.redo();
+        }
+
+        if self.board.is_complete() && self.mode != State::Review {
+            self.mode = State::GameOver;
+        }
+    }
+
+    fn save(&self) {
+        if self.mode == State::OnePlayer || self.mode == State::TwoPlayers {
+            if let Err(e) = GameRecord::from_board(&self.board, self.mode.clone()).save(SAVE_FILE) {
+                println!("Could not save the game: {}", e);
+            }
+        }
+    }
+
+    fn load(&mut self) {
+        let loaded = GameRecord::load(SAVE_FILE)
+            .and_then(|record| record.to_board().map(|board| (record.get_mode(), board)));
+
+        match loaded {
+            Ok((mode, board)) => {
+                self.stop_computer_turn();
+                self.forget_engines();
+                self.view = BoardView::new(&board, WINDOW_WIDTH, WINDOW_HEIGHT, X_INITIAL_OFFSET, Y_INITIAL_OFFSET);
+                self.board = board;
+                self.mode = mode;
+
+                if self.mode == State::OnePlayer {
+                    self.start_computer_turn();
+                }
+
+                if self.board.is_complete() {
+                    self.mode = State::GameOver;
+                }
+            }
+            Err(e) => println!("Could not load the game: {}", e),
         }
     }
 }
 
 impl event::EventHandler for GameState {
     fn update(&mut self, _ctx: &mut Context) -> GameResult {
+        self.update_analysis();
+
+        if self.mode == State::ComputerVsComputer && !self.paused && self.last_move.elapsed() >= self.move_delay {
+            self.start_computer_turn();
+        }
+
+        let computer_move = self.worker.as_mut().and_then(|worker| worker.poll());
+
+        if let Some((engine, result)) = computer_move {
+            self.worker = None;
+            self.keep_engine(self.board.get_current_player(), engine);
+
+            if let Err(e) = result.and_then(|edge| self.board.play(edge)) {
+                println!("The computer could not move: {}", e);
+                return Ok(());
+            }
+            self.last_move = Instant::now();
+
+            if self.board.is_complete() {
+                self.end_game();
+            } else if self.mode == State::OnePlayer {
+                self.start_computer_turn();
+            }
+        }
+
         Ok(())
     }
 
@@ -44,9 +369,27 @@ impl event::EventHandler for GameState {
         graphics::clear(ctx, [0.4, 0.1, 1.0, 1.0].into());
 
         if self.mode == State::None {
-            self.main_menu.draw(ctx)?;
+            self.main_menu.draw(ctx, &self.settings, &self.player_1_settings)?;
         } else if self.mode == State::OnePlayer || self.mode == State::TwoPlayers {
-            self.board.draw(ctx, self.next)?;
+            self.view.draw(ctx, &self.board, self.board.get_current_player())?;
+
+            if self.worker.is_some() {
+                self.view.draw_message(ctx, "Thinking...")?;
+            } else if self.analysis_worker.is_some() {
+                self.view.draw_message(ctx, "Looking for a hint...")?;
+            } else if let Some(Ok(hint)) = self.analysis.as_ref().map(|(_, analysis)| Hint::from_analysis(analysis)) {
+                self.view.draw_message(ctx, &format!("Hint: {}", hint))?;
+            }
+        } else if self.mode == State::ComputerVsComputer {
+            self.view.draw(ctx, &self.board, self.board.get_current_player())?;
+            self.view.draw_message(ctx, &self.get_computers_message())?;
+        } else if self.mode == State::Review {
+            // the move which was played is shown as if it was about to be drawn, the best one as a hint
+            self.view.set_temp_line(self.review.get(self.board.get_history().len()).cloned());
+            self.view.draw(ctx, &self.board, self.board.get_current_player())?;
+            self.view.draw_message(ctx, &self.get_review_message())?;
+        } else if self.mode == State::Stats {
+            self.stats_screen.draw(ctx, &self.ratings, &self.profile)?;
         } else if self.mode == State::GameOver {
             self.end_menu.draw(ctx, self.board.get_marked_by_player_1().len() as u8, self.board.get_marked_by_player_2().len() as u8)?;
         }
@@ -59,12 +402,18 @@ impl event::EventHandler for GameState {
         if Instant::now() - self.last_update >= Duration::from_millis(MILLIS_PER_UPDATE) {
             ggez::input::mouse::set_cursor_type(_ctx, ggez::input::mouse::MouseCursor::Default);
             if self.mode == State::OnePlayer || self.mode == State::TwoPlayers || 
-                    (self.mode == State::None && (self.main_menu.is_on_one_player_entry(x, y) || self.main_menu.is_on_two_player_entry(x, y))) ||
-                    (self.mode == State::GameOver && self.end_menu.is_on_restart(x, y)) {
+                    (self.mode == State::None && (self.main_menu.is_on_one_player_entry(x, y) || self.main_menu.is_on_two_player_entry(x, y) ||
+                        self.main_menu.is_on_computers_entry(x, y) || self.main_menu.is_on_difficulty_entry(x, y) ||
+                        self.main_menu.is_on_engine_entry(x, y) || self.main_menu.is_on_player_1_engine_entry(x, y) ||
+                        self.main_menu.is_on_stats_entry(x, y))) ||
+                    (self.mode == State::Stats && self.stats_screen.is_on_back(x, y)) ||
+                    (self.mode == State::GameOver && (self.end_menu.is_on_restart(x, y) || self.end_menu.is_on_review(x, y))) {
                 ggez::input::mouse::set_cursor_type(_ctx, ggez::input::mouse::MouseCursor::Hand);
             }
             
-            self.board.update_line(self.next, x, y);
+            if self.mode != State::Review && self.mode != State::ComputerVsComputer {
+                self.view.update_line(&self.board, x, y);
+            }
         }
     }
 
@@ -74,63 +423,54 @@ impl event::EventHandler for GameState {
                 self.mode = State::OnePlayer;
             } else if self.main_menu.is_on_two_player_entry(_x, _y) {
                 self.mode = State::TwoPlayers;
+            } else if self.main_menu.is_on_computers_entry(_x, _y) {
+                self.mode = State::ComputerVsComputer;
+                self.paused = false;
+                self.last_move = Instant::now();
+            } else if self.main_menu.is_on_difficulty_entry(_x, _y) {
+                let difficulty = self.settings.get_difficulty().next();
+                self.settings.set_difficulty(difficulty);
+            } else if self.main_menu.is_on_engine_entry(_x, _y) {
+                let engine = self.settings.get_engine().next();
+                self.settings.set_engine(engine);
+            } else if self.main_menu.is_on_player_1_engine_entry(_x, _y) {
+                let engine = self.player_1_settings.get_engine().next();
+                self.player_1_settings.set_engine(engine);
+            } else if self.main_menu.is_on_stats_entry(_x, _y) {
+                self.mode = State::Stats;
+            }
+        } else if self.mode == State::Stats {
+            if self.stats_screen.is_on_back(_x, _y) {
+                self.mode = State::None;
             }
         } else if self.mode == State::OnePlayer {
-            if !self.board.contains_line(&self.board.get_temp_line()) {
-                let previous_score = self.board.get_marked_by_player_1().len();
-
-                self.board.add_line(self.board.get_temp_line());
-                self.board.update_squares(Player::Player1);
-    
-                if self.board.get_marked_by_player_1().len() == previous_score {
-                    let mut previous_score = self.board.get_marked_by_player_2().len();
-
-                    let computer_move = MinMax::alphabeta(&self.board, 6, std::i32::MIN, std::i32::MAX, true);
-                    self.board = computer_move.0;
-                    let mut current_score = self.board.get_marked_by_player_2().len();
-    
-                    while previous_score != current_score {
-                        previous_score = current_score;
-                        let computer_move = MinMax::alphabeta(&self.board, 6, std::i32::MIN, std::i32::MAX, true);
-                        self.board = computer_move.0;
-                        current_score = self.board.get_marked_by_player_2().len();
-                    }
+            if let Some(edge) = self.view.get_temp_line() {
+                if self.board.get_current_player() == Player::Player1 && self.board.play(edge).is_ok() {
+                    self.start_computer_turn();
                 }
+            }
 
-                if self.board.is_complete() {
-                    self.mode = State::GameOver;
-                }
+            if self.board.is_complete() {
+                self.end_game();
             }
         } else if self.mode == State::TwoPlayers {
-            if !self.board.contains_line(&self.board.get_temp_line()) {
-                if self.next == Player::Player1 {
-                    let previous = self.board.get_marked_by_player_1().len();
-                    self.board.add_line(self.board.get_temp_line());
-                    self.board.update_squares(Player::Player1);
-
-                    if self.board.get_marked_by_player_1().len() == previous {
-                        self.next = Player::Player2;
-                    }
-                } else if self.next == Player::Player2 {
-                    let previous = self.board.get_marked_by_player_2().len();
-                    self.board.add_line(self.board.get_temp_line());
-                    self.board.update_squares(Player::Player2);
-
-                    if self.board.get_marked_by_player_2().len() == previous {
-                        self.next = Player::Player1;
-                    }
-                }
+            if let Some(edge) = self.view.get_temp_line() {
+                let _ = self.board.play(edge);
             }
 
             if self.board.is_complete() {
-                self.mode = State::GameOver;
+                self.end_game();
             }
-        } else if self.mode == State::GameOver {
+        } else if self.mode == State::GameOver && self.end_menu.is_on_review(_x, _y) {
+            self.start_review();
+        } else if self.mode == State::GameOver && self.end_menu.is_on_restart(_x, _y) {
+            self.stop_computer_turn();
+            self.forget_engines();
 
-            if self.end_menu.is_on_restart(_x, _y) {
+            if let Ok(board) = Board::new(WIDTH, HEIGHT) {
                 self.mode = State::None;
-                self.board = Board::new(WIDTH, HEIGHT, WINDOW_WIDTH, WINDOW_HEIGHT, X_INITIAL_OFFSET, Y_INITIAL_OFFSET);
-                self.next = Player::Player1;
+                self.view = BoardView::new(&board, WINDOW_WIDTH, WINDOW_HEIGHT, X_INITIAL_OFFSET, Y_INITIAL_OFFSET);
+                self.board = board;
             }
         }
     }
@@ -143,10 +483,33 @@ impl event::EventHandler for GameState {
         _repeat: bool,
     ) {
         match keycode {
-            event::KeyCode::Escape => event::quit(_ctx),
+            KeyCode::Escape => {
+                self.stop_computer_turn();
+                self.analysis_worker = None;
+                event::quit(_ctx);
+            }
+            KeyCode::Z if _keymod.contains(KeyMods::CTRL) => self.undo(),
+            KeyCode::Y if _keymod.contains(KeyMods::CTRL) => self.redo(),
+            KeyCode::S if _keymod.contains(KeyMods::CTRL) => self.save(),
+            KeyCode::L if _keymod.contains(KeyMods::CTRL) => self.load(),
+            KeyCode::H => self.start_hint(),
+            KeyCode::Left if self.mode == State::Review => self.undo(),
+            KeyCode::Right if self.mode == State::Review => self.redo(),
+            KeyCode::Return if self.mode == State::Review => self.stop_review(),
+            KeyCode::Space if self.mode == State::ComputerVsComputer => self.paused = !self.paused,
+            // a paused game goes on one move at a time
+            KeyCode::Right if self.mode == State::ComputerVsComputer && self.paused => self.start_computer_turn(),
+            KeyCode::Up if self.mode == State::ComputerVsComputer => self.change_move_delay(true),
+            KeyCode::Down if self.mode == State::ComputerVsComputer => self.change_move_delay(false),
             _ => (),
         }
     }
+
+    fn quit_event(&mut self, _ctx: &mut Context) -> bool {
+        self.stop_computer_turn();
+        self.analysis_worker = None;
+        false
+    }
 }
 
 pub fn main() {
@@ -163,7 +526,13 @@ pub fn main() {
         }
     );
     let (ctx, event_loop) = &mut cb.build().unwrap();
-    let game_state = &mut GameState::new(ctx);
+    let game_state = &mut match GameState::new(ctx) {
+        Ok(game_state) => game_state,
+        Err(e) => {
+            println!("Error encountered: {}", e);
+            return;
+        }
+    };
 
     if let Err(e) = event::run(ctx, event_loop, game_state) {
         println!("Error encountered: {}", e);
@@ -0,0 +1,222 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::bitboard::BitBoard;
+use crate::chains;
+use crate::entities::{Board, Player, DEFAULT_TIME_LIMIT_MILLIS};
+
+// The usual UCT constant, the square root of two.
+pub const DEFAULT_EXPLORATION: f64 = 1.41;
+
+// How long a search goes on: a fixed number of playouts or a span of time.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Budget {
+    Iterations(u32),
+    Time(Duration),
+}
+
+// One move in the tree. The statistics are kept from the point of view of the
+// player who made the move, which is not simply the opposite of the parent's
+// player, because completing a box gives the same player another turn.
+struct Node {
+    edge: Option<usize>,
+    player: Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<usize>,
+    visits: u32,
+    wins: f64,
+}
+
+impl Node {
+    fn new(edge: Option<usize>, player: Player, parent: Option<usize>, untried: Vec<usize>) -> Node {
+        Node {
+            edge,
+            player,
+            parent,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+}
+
+// Monte Carlo tree search with UCT selection. Playouts take boxes whenever they
+// can and otherwise prefer edges which do not draw the third side of a box.
+pub struct Mcts {
+    exploration: f64,
+    budget: Budget,
+    rng: StdRng,
+    iterations: u32,
+}
+
+impl Mcts {
+    pub fn new(_exploration: f64, _budget: Budget, seed: u64) -> Mcts {
+        Mcts {
+            exploration: _exploration,
+            budget: _budget,
+            rng: StdRng::seed_from_u64(seed),
+            iterations: 0,
+        }
+    }
+
+    pub fn with_budget(budget: Budget) -> Mcts {
+        Mcts::new(DEFAULT_EXPLORATION, budget, rand::random())
+    }
+
+    pub fn get_exploration(&self) -> f64 {
+        self.exploration
+    }
+
+    pub fn get_budget(&self) -> Budget {
+        self.budget
+    }
+
+    // The number of playouts of the last search.
+    pub fn get_iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    // Returns the index of the most visited edge for the player to move, or
+    // None if the board is complete. The stop flag ends the search early.
+    pub fn search(&mut self, board: &Board, stop: &AtomicBool) -> Option<usize> {
+        let mut bitboard = BitBoard::from_board(board);
+        let free = bitboard.get_free_edges();
+        if free.is_empty() {
+            return None;
+        }
+
+        let start = Instant::now();
+        let mut nodes = vec![Node::new(None, Player::Dummy, None, free)];
+        self.iterations = 0;
+
+        while !self.is_exhausted(start) && !stop.load(Ordering::Relaxed) {
+            self.iterate(&mut nodes, &mut bitboard);
+            self.iterations += 1;
+        }
+
+        let best = nodes[0].children.iter().max_by_key(|child| nodes[**child].visits);
+        match best {
+            Some(child) => nodes[*child].edge,
+            None => nodes[0].untried.first().cloned(),
+        }
+    }
+
+    fn is_exhausted(&self, start: Instant) -> bool {
+        match self.budget {
+            Budget::Iterations(iterations) => self.iterations >= iterations,
+            Budget::Time(time_limit) => self.iterations > 0 && start.elapsed() >= time_limit,
+        }
+    }
+
+    fn iterate(&mut self, nodes: &mut Vec<Node>, board: &mut BitBoard) {
+        let depth = board.get_depth();
+        let mut current = 0;
+
+        // selection
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            current = self.select_child(nodes, current);
+            if let Some(edge) = nodes[current].edge {
+                board.make_move(edge);
+            }
+        }
+
+        // expansion
+        if !nodes[current].untried.is_empty() {
+            let index = self.rng.gen_range(0, nodes[current].untried.len());
+            let edge = nodes[current].untried.swap_remove(index);
+            let player = board.get_current_player();
+            board.make_move(edge);
+
+            let child = nodes.len();
+            nodes.push(Node::new(Some(edge), player, Some(current), board.get_free_edges()));
+            nodes[current].children.push(child);
+            current = child;
+        }
+
+        // simulation
+        let winner = self.playout(board);
+
+        // backpropagation
+        let mut node = Some(current);
+        while let Some(index) = node {
+            nodes[index].visits += 1;
+            nodes[index].wins += if winner == Player::Dummy {
+                0.5
+            } else if winner == nodes[index].player {
+                1.0
+            } else {
+                0.0
+            };
+            node = nodes[index].parent;
+        }
+
+        while board.get_depth() > depth {
+            board.unmake_move();
+        }
+    }
+
+    fn select_child(&self, nodes: &[Node], parent: usize) -> usize {
+        let log_visits = (nodes[parent].visits.max(1) as f64).ln();
+        let uct = |child: usize| {
+            let node = &nodes[child];
+            let visits = node.visits.max(1) as f64;
+            node.wins / visits + self.exploration * (log_visits / visits).sqrt()
+        };
+
+        nodes[parent].children.iter().cloned()
+            .fold(None, |best: Option<(usize, f64)>, child| {
+                let value = uct(child);
+                match best {
+                    Some(best) if best.1 >= value => Some(best),
+                    _ => Some((child, value)),
+                }
+            })
+            .map_or(nodes[parent].children[0], |best| best.0)
+    }
+
+    // Plays the game to the end and returns the winner, Dummy for a draw.
+    fn playout(&mut self, board: &mut BitBoard) -> Player {
+        while !board.is_complete() {
+            let free = board.get_free_edges();
+            let captures: Vec<usize> = free.iter().cloned().filter(|edge| board.get_captures(*edge) > 0).collect();
+
+            let candidates = if !captures.is_empty() {
+                captures
+            } else {
+                let safe = chains::get_safe_moves(board);
+                if safe.is_empty() {
+                    free
+                } else {
+                    safe
+                }
+            };
+
+            match candidates.choose(&mut self.rng) {
+                Some(edge) => board.make_move(*edge),
+                None => break,
+            };
+        }
+
+        let score_1 = board.get_score(Player::Player1);
+        let score_2 = board.get_score(Player::Player2);
+        if score_1 > score_2 {
+            Player::Player1
+        } else if score_2 > score_1 {
+            Player::Player2
+        } else {
+            Player::Dummy
+        }
+    }
+}
+
+impl Default for Mcts {
+    fn default() -> Self {
+        Mcts::with_budget(Budget::Time(Duration::from_millis(DEFAULT_TIME_LIMIT_MILLIS)))
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::bitboard::BitBoard;
+use crate::entities::{Board, MinMax, Player};
+use crate::search::{self, SearchContext};
+use crate::transposition::{Bound, Entry, SharedTable};
+
+// One thread per core, or a single one if that cannot be found out.
+pub fn get_available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+// Alpha-beta split at the root: the moves of the root are ordered as in the
+// serial search and handed out one at a time to the threads, each of which
+// searches them on its own board. The threads share one transposition table,
+// so a position reached through different root moves is searched only once,
+// and the value of the best root move found so far, which narrows the window of
+// the moves searched after it. Only moves which beat that window have an exact
+// value, and the first of the best of those in search order is chosen. With a
+// single thread this is the serial search, with more the value is the same but
+// another move as good as the serial one may be chosen. The table is kept
+// between searches and gets the result of the root as well, so the next level
+// of iterative deepening starts with the best move of the last one.
+pub struct ParallelSearch {
+    threads: usize,
+    table: SharedTable,
+    nodes: u64,
+}
+
+impl ParallelSearch {
+    pub fn new(_threads: usize) -> ParallelSearch {
+        ParallelSearch {
+            threads: _threads.max(1),
+            table: SharedTable::default(),
+            nodes: 0,
+        }
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn get_table(&self) -> &SharedTable {
+        &self.table
+    }
+
+    // The number of nodes all threads visited in the last search.
+    pub fn get_nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    // Returns the index of the best edge for the player to move together with
+    // its value, as MinMax::search would, or None if the search ran out of time
+    // or got stopped.
+    pub fn search(&mut self, board: &BitBoard, max_depth: u8, deadline: Option<Instant>, stop: &AtomicBool)
+            -> Option<(Option<usize>, i32)> {
+        let mut context = SearchContext::with_shared_table(&self.table, None, stop);
+        let hash_move = context.probe(board.get_hash()).and_then(|entry| entry.get_best());
+        let children = MinMax::order_moves(board, &context, hash_move);
+        if board.is_complete() || max_depth == 0 || children.is_empty() {
+            let mut leaf = board.clone();
+            let is_max = board.get_current_player() == Player::Player2;
+            return Some(MinMax::search(&mut leaf, &mut context, max_depth, i32::MIN, i32::MAX, is_max));
+        }
+
+        let next = AtomicUsize::new(0);
+        let values: Mutex<Vec<Option<(i32, bool)>>> = Mutex::new(vec![None; children.len()]);
+        let nodes = AtomicU64::new(0);
+        let aborted = AtomicBool::new(false);
+        let is_max = board.get_current_player() == Player::Player2;
+        let bound = AtomicI32::new(if is_max {
+                i32::MIN
+            } else {
+                i32::MAX
+            });
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads {
+                let (table, children, next, values, nodes, aborted, bound) =
+                    (&self.table, &children, &next, &values, &nodes, &aborted, &bound);
+
+                scope.spawn(move || {
+                    let mut board = board.clone();
+                    let mut context = SearchContext::with_shared_table(table, deadline, stop);
+
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= children.len() || aborted.load(Ordering::Relaxed) {
+                            break;
+                        }
+
+                        let (alpha, beta) = if is_max {
+                            (bound.load(Ordering::Relaxed), i32::MAX)
+                        } else {
+                            (i32::MIN, bound.load(Ordering::Relaxed))
+                        };
+
+                        board.make_move(children[index]);
+                        let child_is_max = board.get_current_player() == Player::Player2;
+                        let (_, value) = MinMax::search(&mut board, &mut context, max_depth - 1, alpha, beta, child_is_max);
+                        board.unmake_move();
+
+                        if context.is_aborted() {
+                            aborted.store(true, Ordering::Relaxed);
+                            break;
+                        }
+
+                        if is_max {
+                            bound.fetch_max(value, Ordering::Relaxed);
+                        } else {
+                            bound.fetch_min(value, Ordering::Relaxed);
+                        }
+
+                        // a move which does not beat the window is only known to be no better
+                        let exact = if is_max {
+                            value > alpha
+                        } else {
+                            value < beta
+                        };
+
+                        if let Ok(mut values) = values.lock() {
+                            values[index] = Some((value, exact));
+                        }
+                    }
+
+                    nodes.fetch_add(context.get_nodes(), Ordering::Relaxed);
+                });
+            }
+        });
+
+        self.nodes = nodes.load(Ordering::Relaxed);
+        if aborted.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        // the first of equally good moves in search order wins, as in the serial search
+        let values = values.into_inner().ok()?;
+        let mut result = (None, if is_max {
+                i32::MIN
+            } else {
+                i32::MAX
+            });
+
+        for (edge, value) in children.into_iter().zip(values) {
+            let (value, exact) = value?;
+            if !exact {
+                continue;
+            }
+
+            if (is_max && value > result.1) || (!is_max && value < result.1) {
+                result = (Some(edge), value);
+            }
+        }
+
+        // the root was searched with the full window, so the next level starts with its best move
+        self.table.store(Entry::new(board.get_hash(), max_depth, result.1, Bound::Exact, result.0));
+        Some(result)
+    }
+
+    // MinMax::iterative_deepening_with_stop with every level searched in parallel.
+    pub fn iterative_deepening(&mut self, board: &Board, time_limit: Duration, max_depth: u8, stop: &AtomicBool)
+            -> (Board, i32, u8) {
+        let deadline = search::deadline_after(time_limit);
+        let remaining = board.get_edges().len() - board.get_lines().len();
+        let bitboard = BitBoard::from_board(board);
+        let mut result = (board.clone(), 0, 0);
+        let mut nodes = 0;
+
+        for depth in 1..=remaining.min(max_depth.max(1) as usize) as u8 {
+            let found = self.search(&bitboard, depth, if depth == 1 {
+                    None
+                } else {
+                    deadline
+                }, stop);
+            nodes += self.nodes;
+
+            let (best, value) = match found {
+                Some(found) => found,
+                None => break,
+            };
+
+            let mut child = board.clone();
+            if let Some(edge) = best {
+                if child.play(bitboard.get_edge(edge)).is_err() {
+                    break;
+                }
+            }
+            result = (child, value, depth);
+
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+        }
+
+        self.nodes = nodes;
+        result
+    }
+}
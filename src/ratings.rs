@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::difficulty::Difficulty;
+use crate::error::{Error, Result};
+use crate::record::GameRecord;
+use crate::tournament::PairingResult;
+
+pub const INITIAL_RATING: f64 = 1500.0;
+
+// How far a single game moves a rating.
+pub const K_FACTOR: f64 = 32.0;
+
+// The expected score of a player against an opponent, between 0 for a sure
+// loss and 1 for a sure win.
+pub fn get_expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rating {
+    value: f64,
+    wins: u32,
+    draws: u32,
+    losses: u32,
+}
+
+impl Rating {
+    pub fn new(_value: f64, _wins: u32, _draws: u32, _losses: u32) -> Rating {
+        Rating {
+            value: _value,
+            wins: _wins,
+            draws: _draws,
+            losses: _losses,
+        }
+    }
+
+    pub fn get_value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn get_games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn get_wins(&self) -> u32 {
+        self.wins
+    }
+
+    pub fn get_draws(&self) -> u32 {
+        self.draws
+    }
+
+    pub fn get_losses(&self) -> u32 {
+        self.losses
+    }
+
+    // Adds games which together scored the given points, a win being worth one
+    // and a draw a half, against an opponent of the given rating.
+    fn update(&mut self, opponent: f64, wins: u32, draws: u32, losses: u32) {
+        let games = (wins + draws + losses) as f64;
+        let score = wins as f64 + draws as f64 / 2.0;
+        self.value += K_FACTOR * (score - games * get_expected_score(self.value, opponent));
+        self.wins += wins;
+        self.draws += draws;
+        self.losses += losses;
+    }
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating::new(INITIAL_RATING, 0, 0, 0)
+    }
+}
+
+// Elo ratings of people and engines by name. Each computer level is rated
+// under its own name, see get_level_name, and starts from a preset until it
+// has played.
+//
+// The text form has one player per line, the fields separated by tabs:
+//
+//     Alice	1532.4	7	1	4
+//     Computer (Medium)	1467.6	4	1	7
+//
+// which are the name, the rating and the number of wins, draws and losses. A
+// backslash, tab or line break in a name is written as \\, \t, \n or \r.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Ratings {
+    players: BTreeMap<String, Rating>,
+}
+
+impl Ratings {
+    pub fn new() -> Ratings {
+        Ratings {
+            players: BTreeMap::new(),
+        }
+    }
+
+    pub fn get_level_name(difficulty: Difficulty) -> String {
+        format!("Computer ({})", difficulty)
+    }
+
+    fn get_level_preset(difficulty: Difficulty) -> f64 {
+        match difficulty {
+            Difficulty::Easy => 1100.0,
+            Difficulty::Medium => 1400.0,
+            Difficulty::Hard => 1700.0,
+            Difficulty::Expert => 2000.0,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Rating> {
+        self.players.get(name)
+    }
+
+    pub fn get_rating(&self, name: &str) -> f64 {
+        self.get(name).map_or(INITIAL_RATING, |rating| rating.get_value())
+    }
+
+    pub fn get_level_rating(&self, difficulty: Difficulty) -> f64 {
+        self.get(&Ratings::get_level_name(difficulty))
+            .map_or(Ratings::get_level_preset(difficulty), |rating| rating.get_value())
+    }
+
+    // Every player, the highest rated first.
+    pub fn get_players(&self) -> Vec<(&str, &Rating)> {
+        let mut players: Vec<(&str, &Rating)> = self.players.iter().map(|(name, rating)| (name.as_str(), rating)).collect();
+        players.sort_by(|a, b| b.1.get_value().partial_cmp(&a.1.get_value()).unwrap_or(std::cmp::Ordering::Equal));
+        players
+    }
+
+    // Both ratings are updated from the ones before the game.
+    pub fn add_game(&mut self, player_1: &str, player_2: &str, score_1: usize, score_2: usize) {
+        let (wins, losses) = if score_1 > score_2 {
+            (1, 0)
+        } else if score_1 < score_2 {
+            (0, 1)
+        } else {
+            (0, 0)
+        };
+        self.add_games(player_1, player_2, wins, 1 - wins - losses, losses);
+    }
+
+    // Several games between the same two players, counted from the first one's side.
+    pub fn add_games(&mut self, player_1: &str, player_2: &str, wins: u32, draws: u32, losses: u32) {
+        let rating_1 = self.get_initial(player_1);
+        let rating_2 = self.get_initial(player_2);
+
+        self.players.entry(String::from(player_1)).or_insert_with(|| Rating::new(rating_1, 0, 0, 0))
+            .update(rating_2, wins, draws, losses);
+        self.players.entry(String::from(player_2)).or_insert_with(|| Rating::new(rating_2, 0, 0, 0))
+            .update(rating_1, losses, draws, wins);
+    }
+
+    fn get_initial(&self, name: &str) -> f64 {
+        match Difficulty::ALL.iter().find(|difficulty| Ratings::get_level_name(**difficulty) == name) {
+            Some(difficulty) => self.get_level_rating(*difficulty),
+            None => self.get_rating(name),
+        }
+    }
+
+    pub fn add_record(&mut self, record: &GameRecord) -> Result<()> {
+        let (score_1, score_2) = record.get_result()
+            .ok_or_else(|| Error::InvalidRecord(String::from("only finished games can be rated")))?;
+        self.add_game(record.get_player_1(), record.get_player_2(), score_1, score_2);
+        Ok(())
+    }
+
+    // The engines are rated under their configurations, e.g. "minmax:4". A
+    // tournament reports every pairing from both sides, only one of them
+    // should be added.
+    pub fn add_pairing(&mut self, result: &PairingResult) {
+        self.add_games(&result.get_engine().to_string(), &result.get_opponent().to_string(),
+            result.get_wins(), result.get_draws(), result.get_losses());
+    }
+
+    // The computer level whose rating is closest to the player's, or the
+    // default level for someone who has not played yet.
+    pub fn suggest_difficulty(&self, name: &str) -> Difficulty {
+        let rating = match self.get(name) {
+            Some(rating) if rating.get_games() > 0 => rating.get_value(),
+            _ => return Difficulty::default(),
+        };
+
+        Difficulty::ALL.iter().cloned()
+            .min_by(|a, b| {
+                let distance_a = (self.get_level_rating(*a) - rating).abs();
+                let distance_b = (self.get_level_rating(*b) - rating).abs();
+                distance_a.partial_cmp(&distance_b).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Ratings> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    // No file yet simply means nobody has been rated.
+    pub fn load_or_new<P: AsRef<Path>>(path: P) -> Result<Ratings> {
+        match fs::read_to_string(path) {
+            Ok(text) => text.parse(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Ratings::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl fmt::Display for Ratings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, rating) in &self.players {
+            writeln!(f, "{}\t{:.1}\t{}\t{}\t{}", escape_name(name), rating.value, rating.wins, rating.draws,
+                rating.losses)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Ratings {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Ratings> {
+        let mut ratings = Ratings::new();
+
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let malformed = || Error::InvalidRatings(format!("malformed rating '{}'", line));
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 5 {
+                return Err(malformed());
+            }
+
+            let value = fields[1].parse().map_err(|_| malformed())?;
+            let wins = fields[2].parse().map_err(|_| malformed())?;
+            let draws = fields[3].parse().map_err(|_| malformed())?;
+            let losses = fields[4].parse().map_err(|_| malformed())?;
+            let name = unescape_name(fields[0]).ok_or_else(malformed)?;
+            ratings.players.insert(name, Rating::new(value, wins, draws, losses));
+        }
+
+        Ok(ratings)
+    }
+}
+
+// Names are written with backslash escapes for the characters which would
+// break the line into other fields or lines.
+fn escape_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_name(escaped: &str) -> Option<String> {
+    let mut name = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            name.push(c);
+            continue;
+        }
+
+        name.push(match chars.next()? {
+            '\\' => '\\',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(name)
+}
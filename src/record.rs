@@ -0,0 +1,288 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::entities::{Board, Edge, Player, State, PLAYER_1, PLAYER_2};
+use crate::error::{Error, Result};
+
+// Everything needed to restore a game: the moves are replayed on an empty
+// board, so the captured boxes never have to be stored.
+//
+// The text form is a list of headers followed by the moves in edge notation:
+//
+//     [Player1 "Alice"]
+//     [Player2 "Bob"]
+//     [Size "3x3"]
+//     [Mode "TwoPlayers"]
+//     [Turn "Player2"]
+//     [Result "*"]
+//
+//     h0,0 h1,0 v0,0 v0,1
+//
+// The size is given in dots and the result is either "*" for an unfinished game
+// or the number of boxes of each player, e.g. "3-1".
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    width: usize,
+    height: usize,
+    mode: State,
+    player_1: String,
+    player_2: String,
+    moves: Vec<Edge>,
+    current: Player,
+    result: Option<(usize, usize)>,
+}
+
+impl GameRecord {
+    pub fn new(board: &Board, _mode: State, _player_1: String, _player_2: String) -> GameRecord {
+        GameRecord {
+            width: board.get_width(),
+            height: board.get_height(),
+            mode: _mode,
+            player_1: _player_1,
+            player_2: _player_2,
+            moves: board.get_history().iter().map(|outcome| outcome.get_edge()).collect(),
+            current: board.get_current_player(),
+            result: GameRecord::get_board_result(board),
+        }
+    }
+
+    pub fn from_board(board: &Board, mode: State) -> GameRecord {
+        GameRecord::new(board, mode, String::from(PLAYER_1), String::from(PLAYER_2))
+    }
+
+    fn get_board_result(board: &Board) -> Option<(usize, usize)> {
+        if board.is_complete() {
+            Some((board.get_marked_by_player_1().len(), board.get_marked_by_player_2().len()))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_mode(&self) -> State {
+        self.mode.clone()
+    }
+
+    pub fn get_player_1(&self) -> &str {
+        &self.player_1
+    }
+
+    pub fn get_player_2(&self) -> &str {
+        &self.player_2
+    }
+
+    pub fn get_moves(&self) -> &[Edge] {
+        &self.moves
+    }
+
+    pub fn get_current_player(&self) -> Player {
+        self.current
+    }
+
+    pub fn get_result(&self) -> Option<(usize, usize)> {
+        self.result
+    }
+
+    pub fn to_board(&self) -> Result<Board> {
+        let mut board = Board::new(self.width, self.height)?;
+
+        for edge in &self.moves {
+            board.play(*edge)?;
+        }
+
+        if board.get_current_player() != self.current {
+            return Err(Error::InvalidRecord(format!("expected {:?} to move, the moves give {:?}",
+                self.current, board.get_current_player())));
+        }
+
+        if GameRecord::get_board_result(&board) != self.result {
+            return Err(Error::InvalidRecord(String::from("the result does not match the moves")));
+        }
+
+        Ok(board)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GameRecord> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "[Player1 \"{}\"]", escape_value(&self.player_1))?;
+        writeln!(f, "[Player2 \"{}\"]", escape_value(&self.player_2))?;
+        writeln!(f, "[Size \"{}x{}\"]", self.width, self.height)?;
+        writeln!(f, "[Mode \"{:?}\"]", self.mode)?;
+        writeln!(f, "[Turn \"{:?}\"]", self.current)?;
+        match self.result {
+            Some((player_1, player_2)) => writeln!(f, "[Result \"{}-{}\"]", player_1, player_2)?,
+            None => writeln!(f, "[Result \"*\"]")?,
+        }
+        writeln!(f)?;
+
+        let moves: Vec<String> = self.moves.iter().map(|edge| edge.to_string()).collect();
+        writeln!(f, "{}", moves.join(" "))
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<GameRecord> {
+        let mut size = None;
+        let mut mode = None;
+        let mut player_1 = String::from(PLAYER_1);
+        let mut player_2 = String::from(PLAYER_2);
+        let mut current = None;
+        let mut result = None;
+        let mut moves = Vec::new();
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.starts_with('[') {
+                let (key, value) = parse_header(line)?;
+
+                match key {
+                    "Player1" => player_1 = value,
+                    "Player2" => player_2 = value,
+                    "Size" => size = Some(parse_size(&value)?),
+                    "Mode" => mode = Some(parse_mode(&value)?),
+                    "Turn" => current = Some(parse_player(&value)?),
+                    "Result" => result = Some(parse_result(&value)?),
+                    _ => return Err(Error::InvalidRecord(format!("unknown header '{}'", key))),
+                }
+            } else {
+                for notation in line.split_whitespace() {
+                    moves.push(notation.parse()?);
+                }
+            }
+        }
+
+        let (width, height) = size.ok_or_else(|| Error::InvalidRecord(String::from("missing size")))?;
+
+        Ok(GameRecord {
+            width,
+            height,
+            mode: mode.ok_or_else(|| Error::InvalidRecord(String::from("missing mode")))?,
+            player_1,
+            player_2,
+            moves,
+            current: current.ok_or_else(|| Error::InvalidRecord(String::from("missing turn")))?,
+            result: result.unwrap_or(None),
+        })
+    }
+}
+
+fn parse_header(line: &str) -> Result<(&str, String)> {
+    let malformed = || Error::InvalidRecord(format!("malformed header '{}'", line));
+
+    if !line.ends_with(']') {
+        return Err(malformed());
+    }
+
+    let inner = &line[1..line.len() - 1];
+    let mut parts = inner.splitn(2, ' ');
+    let key = parts.next().ok_or_else(malformed)?;
+    let value = parts.next().ok_or_else(malformed)?.trim();
+
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return Err(malformed());
+    }
+
+    let value = unescape_value(&value[1..value.len() - 1]).ok_or_else(malformed)?;
+    Ok((key, value))
+}
+
+// Header values are written with backslash escapes for the characters which
+// would end the value or the line early.
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_value(escaped: &str) -> Option<String> {
+    let mut value = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        // a bare quote would have ended the value
+        if c == '"' {
+            return None;
+        }
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+
+        value.push(match chars.next()? {
+            '\\' => '\\',
+            '"' => '"',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(value)
+}
+
+fn parse_number(value: &str) -> Result<usize> {
+    value.parse().map_err(|_| Error::InvalidRecord(format!("'{}' is not a number", value)))
+}
+
+fn parse_pair(value: &str, separator: char) -> Result<(usize, usize)> {
+    let parts: Vec<&str> = value.split(separator).collect();
+    if parts.len() != 2 {
+        return Err(Error::InvalidRecord(format!("malformed value '{}'", value)));
+    }
+
+    Ok((parse_number(parts[0])?, parse_number(parts[1])?))
+}
+
+fn parse_size(value: &str) -> Result<(usize, usize)> {
+    parse_pair(value, 'x')
+}
+
+fn parse_result(value: &str) -> Result<Option<(usize, usize)>> {
+    if value == "*" {
+        Ok(None)
+    } else {
+        parse_pair(value, '-').map(Some)
+    }
+}
+
+fn parse_mode(value: &str) -> Result<State> {
+    match value {
+        "OnePlayer" => Ok(State::OnePlayer),
+        "TwoPlayers" => Ok(State::TwoPlayers),
+        _ => Err(Error::InvalidRecord(format!("unknown mode '{}'", value))),
+    }
+}
+
+fn parse_player(value: &str) -> Result<Player> {
+    match value {
+        "Player1" => Ok(Player::Player1),
+        "Player2" => Ok(Player::Player2),
+        _ => Err(Error::InvalidRecord(format!("unknown player '{}'", value))),
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::transposition::{Entry, SharedTable, TranspositionTable};
+
+// How often the clock is looked at while searching, in nodes.
+const NODES_PER_TIME_CHECK: u64 = 1024;
+
+// The point in time a search which starts now has to give up at. A time limit
+// too long to add up to a point in time is no limit at all.
+pub fn deadline_after(time_limit: Duration) -> Option<Instant> {
+    Instant::now().checked_add(time_limit)
+}
+
+// How many moves which caused a cutoff are remembered for each ply.
+pub const KILLERS_PER_PLY: usize = 2;
+
+// A search either has a table of its own or shares one with other threads.
+enum Table<'a> {
+    Local(&'a mut TranspositionTable),
+    Shared(&'a SharedTable),
+}
+
+// The state shared by all nodes of one search: the transposition table, the
+// point in time at which the search has to give up, a flag through which another
+// thread can stop it and how many nodes it visited. It also keeps what the move
+// ordering learns along the way: the quiet moves which caused a cutoff at each
+// ply, the killers, and a history score for every edge which grows with the
+// depth of the cutoffs it caused. Leaves are scored the way MinMax always has,
+// or, with the margin evaluation, as player 2's boxes minus player 1's.
+pub struct SearchContext<'a> {
+    table: Table<'a>,
+    deadline: Option<Instant>,
+    stop: Option<&'a AtomicBool>,
+    nodes: u64,
+    aborted: bool,
+    move_ordering: bool,
+    margin_evaluation: bool,
+    killers: Vec<[Option<usize>; KILLERS_PER_PLY]>,
+    history: Vec<u64>,
+}
+
+impl<'a> SearchContext<'a> {
+    pub fn new(_table: &'a mut TranspositionTable, _deadline: Option<Instant>) -> SearchContext<'a> {
+        SearchContext {
+            table: Table::Local(_table),
+            deadline: _deadline,
+            stop: None,
+            nodes: 0,
+            aborted: false,
+            move_ordering: true,
+            margin_evaluation: false,
+            killers: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn with_stop(_table: &'a mut TranspositionTable, _deadline: Option<Instant>, _stop: &'a AtomicBool) -> SearchContext<'a> {
+        SearchContext {
+            stop: Some(_stop),
+            ..SearchContext::new(_table, _deadline)
+        }
+    }
+
+    pub fn with_shared_table(_table: &'a SharedTable, _deadline: Option<Instant>, _stop: &'a AtomicBool) -> SearchContext<'a> {
+        SearchContext {
+            table: Table::Shared(_table),
+            deadline: _deadline,
+            stop: Some(_stop),
+            nodes: 0,
+            aborted: false,
+            move_ordering: true,
+            margin_evaluation: false,
+            killers: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn probe(&mut self, hash: u64) -> Option<Entry> {
+        match &mut self.table {
+            Table::Local(table) => table.probe(hash),
+            Table::Shared(table) => table.probe(hash),
+        }
+    }
+
+    pub fn store(&mut self, entry: Entry) {
+        match &mut self.table {
+            Table::Local(table) => table.store(entry),
+            Table::Shared(table) => table.store(entry),
+        }
+    }
+
+    pub fn get_nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    // Without move ordering the edges are searched in index order, which is
+    // only useful to see what the ordering saves.
+    pub fn is_move_ordering(&self) -> bool {
+        self.move_ordering
+    }
+
+    pub fn set_move_ordering(&mut self, move_ordering: bool) {
+        self.move_ordering = move_ordering;
+    }
+
+    pub fn is_margin_evaluation(&self) -> bool {
+        self.margin_evaluation
+    }
+
+    pub fn set_margin_evaluation(&mut self, margin_evaluation: bool) {
+        self.margin_evaluation = margin_evaluation;
+    }
+
+    pub fn get_killers(&self, ply: usize) -> [Option<usize>; KILLERS_PER_PLY] {
+        self.killers.get(ply).cloned().unwrap_or([None; KILLERS_PER_PLY])
+    }
+
+    pub fn get_history(&self, edge: usize) -> u64 {
+        self.history.get(edge).cloned().unwrap_or(0)
+    }
+
+    // Remembers a quiet move which caused a cutoff in a search of the given depth.
+    pub fn add_cutoff(&mut self, ply: usize, edge: usize, depth: u8) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; KILLERS_PER_PLY]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(edge) {
+            killers.rotate_right(1);
+            killers[0] = Some(edge);
+        }
+
+        if self.history.len() <= edge {
+            self.history.resize(edge + 1, 0);
+        }
+        self.history[edge] += depth as u64 * depth as u64;
+    }
+
+    // Counts the node and tells whether the search ran out of time or got stopped.
+    pub fn visit(&mut self) -> bool {
+        self.nodes += 1;
+
+        if !self.aborted && self.nodes.is_multiple_of(NODES_PER_TIME_CHECK) {
+            if let Some(deadline) = self.deadline {
+                self.aborted = Instant::now() >= deadline;
+            }
+
+            if let Some(stop) = self.stop {
+                self.aborted = self.aborted || stop.load(Ordering::Relaxed);
+            }
+        }
+
+        self.aborted
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::bitboard::{BitBoard, BitSet};
+use crate::entities::{Board, Edge, Player};
+use crate::error::{Error, Result};
+
+// Positions with more free edges than this are left to the heuristic search.
+pub const DEFAULT_MAX_EDGES: usize = 18;
+
+// How often the clock and the stop flag are looked at while solving, in nodes.
+const NODES_PER_STOP_CHECK: u64 = 1024;
+
+// Where a symmetry moves the dot at the given row and column.
+type DotMap = Box<dyn Fn(usize, usize) -> (usize, usize)>;
+
+// Plays out every line of a position to the end to find the final score margin
+// under perfect play. The margin of the boxes still to be taken depends only on
+// which edges are drawn, not on who owns the boxes or whose turn it is, so
+// positions are remembered by their drawn edges. Positions which are mirror
+// images or rotations of each other share one entry. A solve which runs out of
+// time or gets stopped fails with Error::Stopped and leaves only finished
+// positions in the table.
+pub struct Solver {
+    max_edges: usize,
+    dimensions: (usize, usize),
+    symmetries: Vec<Vec<usize>>,
+    table: HashMap<BitSet, i32>,
+    nodes: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+}
+
+impl Solver {
+    pub fn new(_max_edges: usize) -> Solver {
+        Solver {
+            max_edges: _max_edges,
+            dimensions: (0, 0),
+            symmetries: Vec::new(),
+            table: HashMap::new(),
+            nodes: 0,
+            deadline: None,
+            aborted: false,
+        }
+    }
+
+    pub fn get_max_edges(&self) -> usize {
+        self.max_edges
+    }
+
+    pub fn get_nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn get_table_size(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.table.clear();
+        self.nodes = 0;
+    }
+
+    pub fn can_solve(&self, board: &Board) -> bool {
+        board.get_free_edges().len() <= self.max_edges
+    }
+
+    // The final number of boxes of the player to move minus those of the
+    // opponent, counting the boxes already taken as well.
+    pub fn solve(&mut self, board: &Board) -> Result<i32> {
+        let stop = AtomicBool::new(false);
+        let mut bitboard = self.prepare(board, None)?;
+        let player = bitboard.get_current_player();
+        let taken = bitboard.get_score(player) as i32 - bitboard.get_score(player.get_opponent()) as i32;
+
+        Ok(taken + self.search(&mut bitboard, &stop))
+    }
+
+    // The final margin from the point of view of the given player.
+    pub fn solve_for(&mut self, board: &Board, player: Player) -> Result<i32> {
+        let margin = self.solve(board)?;
+
+        Ok(if board.get_current_player() == player {
+            margin
+        } else {
+            -margin
+        })
+    }
+
+    // Every free edge with the final margin for the player to move if it is drawn.
+    pub fn solve_moves(&mut self, board: &Board) -> Result<Vec<(Edge, i32)>> {
+        self.solve_moves_with_stop(board, None, &AtomicBool::new(false))
+    }
+
+    // Like solve_moves, but gives up once the deadline has passed or the stop flag is set.
+    pub fn solve_moves_with_stop(&mut self, board: &Board, deadline: Option<Instant>, stop: &AtomicBool)
+            -> Result<Vec<(Edge, i32)>> {
+        let mut bitboard = self.prepare(board, deadline)?;
+        let player = bitboard.get_current_player();
+        let taken = bitboard.get_score(player) as i32 - bitboard.get_score(player.get_opponent()) as i32;
+        let mut moves = Vec::new();
+
+        for edge in bitboard.get_free_edges() {
+            let value = self.get_move_value(&mut bitboard, edge, stop);
+            if self.aborted {
+                return Err(Error::Stopped);
+            }
+            moves.push((bitboard.get_edge(edge), taken + value));
+        }
+
+        Ok(moves)
+    }
+
+    pub fn best_move(&mut self, board: &Board) -> Result<(Edge, i32)> {
+        self.best_move_with_stop(board, None, &AtomicBool::new(false))
+    }
+
+    pub fn best_move_with_stop(&mut self, board: &Board, deadline: Option<Instant>, stop: &AtomicBool)
+            -> Result<(Edge, i32)> {
+        self.solve_moves_with_stop(board, deadline, stop)?.into_iter()
+            .fold(None, |best: Option<(Edge, i32)>, (edge, value)| match best {
+                Some(best) if best.1 >= value => Some(best),
+                _ => Some((edge, value)),
+            })
+            .ok_or(Error::GameOver)
+    }
+
+    fn prepare(&mut self, board: &Board, deadline: Option<Instant>) -> Result<BitBoard> {
+        let remaining = board.get_free_edges().len();
+        if remaining > self.max_edges {
+            return Err(Error::TooManyEdges(remaining, self.max_edges));
+        }
+
+        let bitboard = BitBoard::from_board(board);
+        let dimensions = (bitboard.get_width(), bitboard.get_height());
+        if self.dimensions != dimensions {
+            self.table.clear();
+            self.symmetries = Solver::get_symmetries(&bitboard);
+            self.dimensions = dimensions;
+        }
+
+        self.deadline = deadline;
+        self.aborted = false;
+        Ok(bitboard)
+    }
+
+    fn get_move_value(&mut self, board: &mut BitBoard, edge: usize, stop: &AtomicBool) -> i32 {
+        let captured = board.make_move(edge) as i32;
+        let rest = self.search(board, stop);
+        board.unmake_move();
+
+        // whoever completes a box moves again, otherwise the opponent takes over
+        if captured > 0 {
+            captured + rest
+        } else {
+            -rest
+        }
+    }
+
+    // The margin of the boxes still free for the player to move, or nothing
+    // useful once the search got aborted.
+    fn search(&mut self, board: &mut BitBoard, stop: &AtomicBool) -> i32 {
+        self.nodes += 1;
+
+        if !self.aborted && self.nodes.is_multiple_of(NODES_PER_STOP_CHECK) {
+            let expired = self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            self.aborted = expired || stop.load(Ordering::Relaxed);
+        }
+
+        if self.aborted || board.is_complete() {
+            return 0;
+        }
+
+        let key = self.get_key(board.get_drawn());
+        if let Some(value) = self.table.get(&key) {
+            return *value;
+        }
+
+        let mut best = i32::MIN;
+        for edge in board.get_free_edges() {
+            best = best.max(self.get_move_value(board, edge, stop));
+            if self.aborted {
+                return 0;
+            }
+        }
+
+        self.table.insert(key, best);
+        best
+    }
+
+    // The smallest of the drawn edges under all symmetries of the board.
+    fn get_key(&self, drawn: &BitSet) -> BitSet {
+        let mut key = drawn.clone();
+        let edge_count = self.symmetries.first().map_or(0, |permutation| permutation.len());
+
+        for permutation in &self.symmetries {
+            let mut image = BitSet::new(edge_count);
+            for (edge, target) in permutation.iter().enumerate() {
+                if drawn.contains(edge) {
+                    image.insert(*target);
+                }
+            }
+
+            if image.get_words() < key.get_words() {
+                key = image;
+            }
+        }
+
+        key
+    }
+
+    // For every symmetry other than the identity, where each edge ends up.
+    // Boards get mirrored both ways, square ones are turned by quarters as well.
+    fn get_symmetries(board: &BitBoard) -> Vec<Vec<usize>> {
+        let width = board.get_width();
+        let height = board.get_height();
+
+        let mut dot_maps: Vec<DotMap> = vec![
+            Box::new(move |row, col| (row, width - 1 - col)),
+            Box::new(move |row, col| (height - 1 - row, col)),
+            Box::new(move |row, col| (height - 1 - row, width - 1 - col)),
+        ];
+
+        if width == height {
+            dot_maps.push(Box::new(move |row, col| (col, row)));
+            dot_maps.push(Box::new(move |row, col| (width - 1 - col, row)));
+            dot_maps.push(Box::new(move |row, col| (col, height - 1 - row)));
+            dot_maps.push(Box::new(move |row, col| (width - 1 - col, height - 1 - row)));
+        }
+
+        dot_maps.iter()
+            .map(|map| (0..board.get_edge_count())
+                .map(|index| {
+                    let edge = board.get_edge(index);
+                    let start = map(edge.get_row(), edge.get_col());
+                    let end = map(edge.get_end().0, edge.get_end().1);
+                    let (first, second) = if start <= end { (start, end) } else { (end, start) };
+
+                    let image = if first.0 == second.0 {
+                        Edge::horizontal(first.0, first.1)
+                    } else {
+                        Edge::vertical(first.0, first.1)
+                    };
+                    board.get_edge_index(&image)
+                })
+                .collect())
+            .collect()
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver::new(DEFAULT_MAX_EDGES)
+    }
+}
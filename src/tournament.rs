@@ -0,0 +1,370 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::engine::{Engine, EngineKind};
+use crate::entities::{Board, Player, DEFAULT_TIME_LIMIT_MILLIS};
+use crate::error::{Error, Result};
+
+// An engine together with its limits, written as "name[:depth][@millis][/threads]",
+// e.g. "minmax:4@500", "minmax@500/4" or "greedy". The depth limit and the
+// number of threads only apply to MinMax.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EngineConfig {
+    engine: EngineKind,
+    max_depth: u8,
+    time_limit: Duration,
+    threads: usize,
+}
+
+impl EngineConfig {
+    pub fn new(_engine: EngineKind, _max_depth: u8, _time_limit: Duration) -> EngineConfig {
+        EngineConfig {
+            engine: _engine,
+            max_depth: _max_depth,
+            time_limit: _time_limit,
+            threads: 1,
+        }
+    }
+
+    pub fn get_engine(&self) -> EngineKind {
+        self.engine
+    }
+
+    pub fn get_max_depth(&self) -> u8 {
+        self.max_depth
+    }
+
+    pub fn get_time_limit(&self) -> Duration {
+        self.time_limit
+    }
+
+    pub fn get_threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn create_engine(&self) -> Box<dyn Engine> {
+        self.engine.create_with_limits(self.time_limit, self.max_depth, self.threads)
+    }
+}
+
+impl fmt::Display for EngineConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.engine)?;
+        if self.max_depth != u8::MAX {
+            write!(f, ":{}", self.max_depth)?;
+        }
+        if self.time_limit != Duration::from_millis(DEFAULT_TIME_LIMIT_MILLIS) {
+            write!(f, "@{}", self.time_limit.as_millis())?;
+        }
+        if self.threads > 1 {
+            write!(f, "/{}", self.threads)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for EngineConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<EngineConfig> {
+        let invalid = || Error::InvalidEngineConfig(String::from(s));
+
+        let (rest, threads) = match s.find('/') {
+            Some(index) => (&s[..index], s[index + 1..].parse().map_err(|_| invalid())?),
+            None => (s, 1),
+        };
+        let (rest, time_limit) = match rest.find('@') {
+            Some(index) => (&rest[..index], rest[index + 1..].parse().map_err(|_| invalid())?),
+            None => (rest, DEFAULT_TIME_LIMIT_MILLIS),
+        };
+        let (name, max_depth) = match rest.find(':') {
+            Some(index) => (&rest[..index], rest[index + 1..].parse().map_err(|_| invalid())?),
+            None => (rest, u8::MAX),
+        };
+
+        if max_depth == 0 || threads == 0 {
+            return Err(invalid());
+        }
+
+        let mut config = EngineConfig::new(name.parse()?, max_depth, Duration::from_millis(time_limit));
+        config.set_threads(threads);
+        Ok(config)
+    }
+}
+
+// How a single game went. The thinking time of each side is summed over its moves.
+#[derive(Debug, PartialEq, Clone)]
+pub struct GameSummary {
+    scores: (usize, usize),
+    moves: (u32, u32),
+    times: (Duration, Duration),
+}
+
+impl GameSummary {
+    pub fn get_score(&self, player: Player) -> usize {
+        if player == Player::Player1 {
+            self.scores.0
+        } else {
+            self.scores.1
+        }
+    }
+
+    pub fn get_moves(&self, player: Player) -> u32 {
+        if player == Player::Player1 {
+            self.moves.0
+        } else {
+            self.moves.1
+        }
+    }
+
+    pub fn get_time(&self, player: Player) -> Duration {
+        if player == Player::Player1 {
+            self.times.0
+        } else {
+            self.times.1
+        }
+    }
+
+    // The final margin of the player, positive if they won.
+    pub fn get_margin(&self, player: Player) -> i32 {
+        self.get_score(player) as i32 - self.get_score(player.get_opponent()) as i32
+    }
+}
+
+// Plays a game to the end on an empty board, player 1 moving first.
+pub fn play_game(width: usize, height: usize, player_1: &mut dyn Engine, player_2: &mut dyn Engine)
+        -> Result<GameSummary> {
+    let mut board = Board::new(width, height)?;
+    let mut moves = (0, 0);
+    let mut times = (Duration::from_secs(0), Duration::from_secs(0));
+
+    while !board.is_complete() {
+        let player = board.get_current_player();
+        let start = Instant::now();
+        let edge = if player == Player::Player1 {
+            player_1.choose_move(&board, player)?
+        } else {
+            player_2.choose_move(&board, player)?
+        };
+        let elapsed = start.elapsed();
+        board.play(edge)?;
+
+        if player == Player::Player1 {
+            moves.0 += 1;
+            times.0 += elapsed;
+        } else {
+            moves.1 += 1;
+            times.1 += elapsed;
+        }
+    }
+
+    Ok(GameSummary {
+        scores: (board.get_marked_by_player_1().len(), board.get_marked_by_player_2().len()),
+        moves,
+        times,
+    })
+}
+
+// The games of one engine against another, counted from the first one's side.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PairingResult {
+    engine: EngineConfig,
+    opponent: EngineConfig,
+    width: usize,
+    height: usize,
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    margin: i64,
+    moves: u32,
+    time: Duration,
+}
+
+impl PairingResult {
+    pub fn new(_engine: EngineConfig, _opponent: EngineConfig, _width: usize, _height: usize) -> PairingResult {
+        PairingResult {
+            engine: _engine,
+            opponent: _opponent,
+            width: _width,
+            height: _height,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            margin: 0,
+            moves: 0,
+            time: Duration::from_secs(0),
+        }
+    }
+
+    pub fn add_game(&mut self, summary: &GameSummary, player: Player) {
+        let margin = summary.get_margin(player);
+        if margin > 0 {
+            self.wins += 1;
+        } else if margin < 0 {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+
+        self.margin += margin as i64;
+        self.moves += summary.get_moves(player);
+        self.time += summary.get_time(player);
+    }
+
+    pub fn get_engine(&self) -> &EngineConfig {
+        &self.engine
+    }
+
+    pub fn get_opponent(&self) -> &EngineConfig {
+        &self.opponent
+    }
+
+    pub fn get_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn get_games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn get_wins(&self) -> u32 {
+        self.wins
+    }
+
+    pub fn get_draws(&self) -> u32 {
+        self.draws
+    }
+
+    pub fn get_losses(&self) -> u32 {
+        self.losses
+    }
+
+    pub fn get_average_margin(&self) -> f64 {
+        if self.get_games() == 0 {
+            0.0
+        } else {
+            self.margin as f64 / self.get_games() as f64
+        }
+    }
+
+    // The engine's average thinking time per move in milliseconds.
+    pub fn get_average_move_millis(&self) -> f64 {
+        if self.moves == 0 {
+            0.0
+        } else {
+            self.time.as_secs_f64() * 1000.0 / self.moves as f64
+        }
+    }
+}
+
+// Every engine plays every other one the given number of games on a board of
+// the given size. The engines take turns at moving first.
+pub struct Tournament {
+    engines: Vec<EngineConfig>,
+    width: usize,
+    height: usize,
+    games: u32,
+}
+
+impl Tournament {
+    pub fn new(_engines: Vec<EngineConfig>, _width: usize, _height: usize, _games: u32) -> Tournament {
+        Tournament {
+            engines: _engines,
+            width: _width,
+            height: _height,
+            games: _games,
+        }
+    }
+
+    pub fn get_engines(&self) -> &[EngineConfig] {
+        &self.engines
+    }
+
+    pub fn get_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn get_games(&self) -> u32 {
+        self.games
+    }
+
+    // One result for each ordered pair of engines, so each pairing shows up
+    // once from either side.
+    pub fn run(&self) -> Result<Vec<PairingResult>> {
+        let count = self.engines.len();
+        let mut results = vec![None; count * count];
+
+        for i in 0..count {
+            for j in i + 1..count {
+                let (result, reversed) = self.play_pairing(&self.engines[i], &self.engines[j])?;
+                results[i * count + j] = Some(result);
+                results[j * count + i] = Some(reversed);
+            }
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    // The results of the first engine against the second and the other way round.
+    fn play_pairing(&self, first: &EngineConfig, second: &EngineConfig) -> Result<(PairingResult, PairingResult)> {
+        let mut result = PairingResult::new(first.clone(), second.clone(), self.width, self.height);
+        let mut reversed = PairingResult::new(second.clone(), first.clone(), self.width, self.height);
+
+        for game in 0..self.games {
+            let mut engine = first.create_engine();
+            let mut opponent = second.create_engine();
+
+            let (summary, player) = if game % 2 == 0 {
+                (play_game(self.width, self.height, engine.as_mut(), opponent.as_mut())?, Player::Player1)
+            } else {
+                (play_game(self.width, self.height, opponent.as_mut(), engine.as_mut())?, Player::Player2)
+            };
+
+            result.add_game(&summary, player);
+            reversed.add_game(&summary, player.get_opponent());
+        }
+
+        Ok((result, reversed))
+    }
+}
+
+const CSV_HEADER: &str = "size,engine,opponent,games,wins,draws,losses,average_margin,average_move_ms";
+
+pub fn to_csv(results: &[PairingResult]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+
+    for result in results {
+        let (width, height) = result.get_size();
+        csv.push_str(&format!("\n{}x{},{},{},{},{},{},{},{:.2},{:.3}", width, height, result.get_engine(),
+            result.get_opponent(), result.get_games(), result.get_wins(), result.get_draws(), result.get_losses(),
+            result.get_average_margin(), result.get_average_move_millis()));
+    }
+
+    csv
+}
+
+// A JSON array with one object per result, using the same fields as the CSV.
+// Engine configurations only contain letters, digits and ":@/-", so they need
+// no escaping.
+pub fn to_json(results: &[PairingResult]) -> String {
+    let objects: Vec<String> = results.iter()
+        .map(|result| {
+            let (width, height) = result.get_size();
+            format!("  {{\"size\": \"{}x{}\", \"engine\": \"{}\", \"opponent\": \"{}\", \"games\": {}, \"wins\": {}, \
+                \"draws\": {}, \"losses\": {}, \"average_margin\": {:.2}, \"average_move_ms\": {:.3}}}",
+                width, height, result.get_engine(), result.get_opponent(), result.get_games(), result.get_wins(),
+                result.get_draws(), result.get_losses(), result.get_average_margin(), result.get_average_move_millis())
+        })
+        .collect();
+
+    if objects.is_empty() {
+        String::from("[]")
+    } else {
+        format!("[\n{}\n]", objects.join(",\n"))
+    }
+}
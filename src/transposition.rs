@@ -0,0 +1,346 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const ZOBRIST_SEED: u64 = 0x5eed_d075_b0ce_5000;
+
+pub const DEFAULT_TABLE_SIZE: usize = 1 << 18;
+
+// Random keys which are xor-ed together into the hash of a position: one for
+// the size of the board, one for every drawn edge, one for every box per owner
+// and one when player 2 is to move. The keys are drawn from a seed which the
+// size goes into, so positions on boards of different sizes, even empty ones,
+// do not hash alike and cannot share table entries.
+#[derive(Debug, Clone)]
+pub struct Zobrist {
+    board: u64,
+    edges: Vec<u64>,
+    squares_1: Vec<u64>,
+    squares_2: Vec<u64>,
+    player_2: u64,
+}
+
+impl Zobrist {
+    // The keys for a board of width by height dots.
+    pub fn new(width: usize, height: usize) -> Zobrist {
+        let mut rng = StdRng::seed_from_u64(ZOBRIST_SEED ^ ((width as u64) << 32 | height as u64));
+        let edge_count = (width - 1) * height + width * (height - 1);
+        let square_count = (width - 1) * (height - 1);
+
+        Zobrist {
+            board: rng.gen(),
+            edges: (0..edge_count).map(|_| rng.gen()).collect(),
+            squares_1: (0..square_count).map(|_| rng.gen()).collect(),
+            squares_2: (0..square_count).map(|_| rng.gen()).collect(),
+            player_2: rng.gen(),
+        }
+    }
+
+    pub fn get_board(&self) -> u64 {
+        self.board
+    }
+
+    pub fn get_edge(&self, edge: usize) -> u64 {
+        self.edges[edge]
+    }
+
+    pub fn get_square_1(&self, square: usize) -> u64 {
+        self.squares_1[square]
+    }
+
+    pub fn get_square_2(&self, square: usize) -> u64 {
+        self.squares_2[square]
+    }
+
+    pub fn get_player_2(&self) -> u64 {
+        self.player_2
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Entry {
+    hash: u64,
+    depth: u8,
+    value: i32,
+    bound: Bound,
+    best: Option<usize>,
+}
+
+impl Entry {
+    pub fn new(_hash: u64, _depth: u8, _value: i32, _bound: Bound, _best: Option<usize>) -> Entry {
+        Entry {
+            hash: _hash,
+            depth: _depth,
+            value: _value,
+            bound: _bound,
+            best: _best,
+        }
+    }
+
+    pub fn get_hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn get_depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn get_value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn get_bound(&self) -> Bound {
+        self.bound
+    }
+
+    pub fn get_best(&self) -> Option<usize> {
+        self.best
+    }
+
+    // Everything but the hash in one word: the value in the low 32 bits, then
+    // the depth, the bound and the best edge plus one, 0 standing for none.
+    fn pack(&self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        let best = self.best.map_or(0, |edge| edge as u64 + 1) & 0xffff;
+
+        (self.value as u32 as u64) | (self.depth as u64) << 32 | bound << 40 | best << 42
+    }
+
+    fn unpack(hash: u64, data: u64) -> Entry {
+        let bound = match (data >> 40) & 0b11 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        let best = (data >> 42) & 0xffff;
+
+        Entry::new(hash, (data >> 32) as u8, data as u32 as i32, bound, if best == 0 {
+                None
+            } else {
+                Some(best as usize - 1)
+            })
+    }
+}
+
+// A fixed number of slots indexed by the hash. A slot is overwritten by another
+// position, or by the same position searched at least as deep.
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    probes: u64,
+    hits: u64,
+    stores: u64,
+}
+
+impl TranspositionTable {
+    pub fn new(size: usize) -> TranspositionTable {
+        TranspositionTable {
+            entries: vec![None; size.max(1)],
+            probes: 0,
+            hits: 0,
+            stores: 0,
+        }
+    }
+
+    // A table no bigger than the number of positions a search of the given depth
+    // can reach from a board with that many free edges, which is at most the
+    // number of ways to draw up to depth of them, and never bigger than the
+    // default.
+    pub fn for_search(free_edges: usize, depth: u8) -> TranspositionTable {
+        let mut size = 1;
+        let mut ways = 1;
+        for drawn in 1..=free_edges.min(depth as usize) {
+            ways = ways * (free_edges - drawn + 1) / drawn;
+            size += ways;
+            if size >= DEFAULT_TABLE_SIZE {
+                break;
+            }
+        }
+
+        TranspositionTable::new(size.min(DEFAULT_TABLE_SIZE))
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn probe(&mut self, hash: u64) -> Option<Entry> {
+        self.probes += 1;
+        let index = (hash % self.entries.len() as u64) as usize;
+
+        match self.entries[index] {
+            Some(entry) if entry.get_hash() == hash => {
+                self.hits += 1;
+                Some(entry)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn store(&mut self, entry: Entry) {
+        let index = (entry.get_hash() % self.entries.len() as u64) as usize;
+
+        let replace = match self.entries[index] {
+            Some(old) => old.get_hash() != entry.get_hash() || old.get_depth() <= entry.get_depth(),
+            None => true,
+        };
+
+        if replace {
+            self.stores += 1;
+            self.entries[index] = Some(entry);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = None;
+        }
+        self.probes = 0;
+        self.hits = 0;
+        self.stores = 0;
+    }
+
+    pub fn get_probes(&self) -> u64 {
+        self.probes
+    }
+
+    pub fn get_hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn get_stores(&self) -> u64 {
+        self.stores
+    }
+
+    pub fn get_hit_rate(&self) -> f64 {
+        if self.probes == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.probes as f64
+        }
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(DEFAULT_TABLE_SIZE)
+    }
+}
+
+// A table which several threads fill at the same time without locking. Each
+// slot holds the packed entry and, next to it, the hash xor-ed with the packed
+// entry, so a slot which another thread is halfway through overwriting does not
+// match the hash of either position. The replacement rule and the counters are
+// the same as those of TranspositionTable.
+#[derive(Debug)]
+pub struct SharedTable {
+    keys: Vec<AtomicU64>,
+    data: Vec<AtomicU64>,
+    probes: AtomicU64,
+    hits: AtomicU64,
+    stores: AtomicU64,
+}
+
+impl SharedTable {
+    pub fn new(size: usize) -> SharedTable {
+        SharedTable {
+            keys: (0..size.max(1)).map(|_| AtomicU64::new(0)).collect(),
+            data: (0..size.max(1)).map(|_| AtomicU64::new(0)).collect(),
+            probes: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn load(&self, index: usize) -> Option<Entry> {
+        let data = self.data[index].load(Ordering::Relaxed);
+        let hash = self.keys[index].load(Ordering::Relaxed) ^ data;
+
+        // a slot which was never written holds zeros in both words
+        if data == 0 && hash == 0 {
+            None
+        } else {
+            Some(Entry::unpack(hash, data))
+        }
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<Entry> {
+        self.probes.fetch_add(1, Ordering::Relaxed);
+        let index = (hash % self.keys.len() as u64) as usize;
+        let entry = self.load(index).filter(|entry| entry.get_hash() == hash);
+
+        if entry.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        entry
+    }
+
+    pub fn store(&self, entry: Entry) {
+        let index = (entry.get_hash() % self.keys.len() as u64) as usize;
+
+        let replace = match self.load(index) {
+            Some(old) => old.get_hash() != entry.get_hash() || old.get_depth() <= entry.get_depth(),
+            None => true,
+        };
+
+        if replace {
+            self.stores.fetch_add(1, Ordering::Relaxed);
+            let data = entry.pack();
+            self.data[index].store(data, Ordering::Relaxed);
+            self.keys[index].store(entry.get_hash() ^ data, Ordering::Relaxed);
+        }
+    }
+
+    pub fn clear(&self) {
+        for (key, data) in self.keys.iter().zip(self.data.iter()) {
+            key.store(0, Ordering::Relaxed);
+            data.store(0, Ordering::Relaxed);
+        }
+        self.probes.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+        self.stores.store(0, Ordering::Relaxed);
+    }
+
+    pub fn get_probes(&self) -> u64 {
+        self.probes.load(Ordering::Relaxed)
+    }
+
+    pub fn get_hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn get_stores(&self) -> u64 {
+        self.stores.load(Ordering::Relaxed)
+    }
+
+    pub fn get_hit_rate(&self) -> f64 {
+        let probes = self.get_probes();
+        if probes == 0 {
+            0.0
+        } else {
+            self.get_hits() as f64 / probes as f64
+        }
+    }
+}
+
+impl Default for SharedTable {
+    fn default() -> Self {
+        SharedTable::new(DEFAULT_TABLE_SIZE)
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::engine::Engine;
+use crate::entities::{Board, Edge};
+use crate::error::Result;
+
+// A move together with the engine which chose it.
+pub type EngineMove = (Box<dyn Engine>, Result<Edge>);
+
+// Runs a search on a separate thread, so the window keeps responding while
+// the computer thinks. The result is picked up with poll.
+pub struct SearchWorker<T = Result<Edge>> {
+    receiver: Receiver<T>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl SearchWorker {
+    // Lets the engine look for a move of the player whose turn it is on the board.
+    pub fn start(board: Board, mut engine: Box<dyn Engine>) -> SearchWorker {
+        SearchWorker::spawn(move |stop| engine.choose_move_with_stop(&board, board.get_current_player(), stop))
+    }
+}
+
+impl SearchWorker<EngineMove> {
+    // Like start, but hands the engine back with the move, so it keeps what it
+    // learned, e.g. its transposition table, for the next one. A cancelled
+    // worker delivers nothing, so the engine is lost with it.
+    pub fn start_keeping_engine(board: Board, mut engine: Box<dyn Engine>) -> SearchWorker<EngineMove> {
+        SearchWorker::spawn(move |stop| {
+            let result = engine.choose_move_with_stop(&board, board.get_current_player(), stop);
+            (engine, result)
+        })
+    }
+}
+
+impl<T: Send + 'static> SearchWorker<T> {
+    // Runs the job, which should give up once the flag it is handed gets set.
+    pub fn spawn<F: FnOnce(&AtomicBool) -> T + Send + 'static>(job: F) -> SearchWorker<T> {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let handle = thread::spawn(move || {
+            let result = job(&thread_stop);
+            if !thread_stop.load(Ordering::Relaxed) {
+                // the receiver is gone if the worker was dropped in the meantime
+                let _ = sender.send(result);
+            }
+        });
+
+        SearchWorker {
+            receiver,
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl<T> SearchWorker<T> {
+    // The result, once the job is done.
+    pub fn poll(&mut self) -> Option<T> {
+        if self.stop.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        match self.receiver.try_recv() {
+            Ok(result) => {
+                self.join();
+                Some(result)
+            }
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_none()
+    }
+
+    // Stops the job and waits for the thread to end. No result is delivered afterwards.
+    pub fn cancel(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.join();
+    }
+
+    fn join(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl<T> Drop for SearchWorker<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
@@ -0,0 +1,7 @@
+use dotsnboxes::entities::Board;
+
+// Player 1 to move with the bottom left box one line short of complete and the
+// top right box having two sides drawn.
+pub fn get_capture_board() -> Board {
+    Board::from_position_str("3x3|h1,0=1 h2,0=2 v1,0=1 h0,1=2 v0,2=1||1").unwrap()
+}
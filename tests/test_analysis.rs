@@ -0,0 +1,109 @@
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use dotsnboxes::analysis::Analysis;
+use dotsnboxes::entities::{Board, Edge, Player};
+use dotsnboxes::error::Error;
+
+#[test]
+fn test_exact_analysis_late_in_game() {
+    let board = Board::from_position_str("3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2").unwrap();
+
+    let analysis = Analysis::run(&board, Duration::from_millis(100), u8::MAX).unwrap();
+
+    assert!(analysis.is_exact());
+    assert_eq!(analysis.get_player(), Player::Player2);
+    assert_eq!(analysis.get_moves().len(), 2);
+
+    let best = analysis.get_best().unwrap();
+    assert_eq!(best.get_edge(), Edge::vertical(0, 1));
+    assert_eq!(best.get_value(), 2);
+    assert_eq!(best.get_variation(), &[Edge::vertical(0, 2)]);
+
+    assert_eq!(analysis.get_move(Edge::vertical(0, 2)).unwrap().get_value(), -2);
+}
+
+#[test]
+fn test_every_move_is_scored_best_first() {
+    let board = Board::new(4, 4).unwrap();
+
+    let analysis = Analysis::run(&board, Duration::from_millis(200), 3).unwrap();
+
+    assert!(!analysis.is_exact());
+    assert_eq!(analysis.get_moves().len(), board.get_free_edges().len());
+    for edge in board.get_free_edges() {
+        assert!(analysis.get_move(edge).is_some());
+    }
+
+    let values: Vec<i32> = analysis.get_moves().iter().map(|analysis| analysis.get_value()).collect();
+    assert!(values.windows(2).all(|pair| pair[0] >= pair[1]));
+    assert!(analysis.get_nodes() > 0);
+}
+
+#[test]
+fn test_values_are_box_margins() {
+    // too many edges for the solver, and the box at 0,0 is there for the taking
+    let board = Board::from_position_str("4x4|h0,0=1 v0,0=2 h1,0=1||1").unwrap();
+
+    let analysis = Analysis::run(&board, Duration::from_secs(10), 2).unwrap();
+
+    assert!(!analysis.is_exact());
+    let best = analysis.get_best().unwrap();
+    assert_eq!((best.get_edge(), best.get_value()), (Edge::vertical(0, 1), 1));
+    assert_eq!(analysis.get_move(Edge::horizontal(3, 2)).unwrap().get_value(), -1);
+}
+
+#[test]
+fn test_max_depth_is_respected() {
+    let board = Board::new(4, 4).unwrap();
+
+    let analysis = Analysis::run(&board, Duration::from_secs(10), 2).unwrap();
+
+    assert_eq!(analysis.get_depth(), 2);
+    assert!(analysis.get_moves().iter().all(|analysis| analysis.get_variation().len() <= 1));
+}
+
+#[test]
+fn test_large_board_is_analysed() {
+    // 14 * 9 + 13 * 10 = 256 edges, one more than fits into a u8
+    let board = Board::new(14, 10).unwrap();
+    assert_eq!(board.get_free_edges().len(), 256);
+
+    let analysis = Analysis::run(&board, Duration::from_millis(10), 1).unwrap();
+    assert_eq!(analysis.get_depth(), 1);
+    assert_eq!(analysis.get_moves().len(), 256);
+}
+
+#[test]
+fn test_solvable_position_keeps_to_limits() {
+    // few enough edges for the solver, but far too many to solve in no time
+    let board = Board::new(4, 3).unwrap();
+
+    let start = Instant::now();
+    let stopped = Analysis::run_with_stop(&board, Duration::from_secs(60), u8::MAX, &AtomicBool::new(true));
+    assert!(matches!(stopped, Err(Error::Stopped)));
+
+    let analysis = Analysis::run(&board, Duration::from_millis(0), u8::MAX).unwrap();
+    assert!(!analysis.is_exact());
+    assert_eq!(analysis.get_depth(), 1);
+    assert_eq!(analysis.get_moves().len(), board.get_free_edges().len());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_huge_time_limit_means_no_limit() {
+    let board = Board::new(4, 4).unwrap();
+
+    let analysis = Analysis::run(&board, Duration::from_millis(u64::MAX), 2).unwrap();
+    assert_eq!(analysis.get_depth(), 2);
+}
+
+#[test]
+fn test_no_analysis_for_finished_game() {
+    let mut board = Board::new(2, 2).unwrap();
+    for edge in board.get_edges() {
+        board.play(edge).unwrap();
+    }
+
+    assert!(Analysis::run(&board, Duration::from_millis(100), u8::MAX).is_err());
+}
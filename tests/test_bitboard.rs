@@ -0,0 +1,92 @@
+use std::time::Instant;
+
+use dotsnboxes::bitboard::{BitBoard, BitSet};
+use dotsnboxes::entities::{Board, Edge, MinMax, Player, Square};
+
+#[test]
+fn test_bitset() {
+    let mut set = BitSet::new(130);
+
+    set.insert(0);
+    set.insert(64);
+    set.insert(129);
+    assert!(set.contains(64));
+    assert!(!set.contains(65));
+    assert_eq!(3, set.count());
+    assert_eq!(3, set.get_words().len());
+
+    set.remove(64);
+    assert!(!set.contains(64));
+    assert_eq!(2, set.count());
+}
+
+#[test]
+fn test_from_board() {
+    let board = Board::from_position_str("3x2|h0,0=1 h1,0=2 v0,0=1 v0,1=2|0,0=2|2").unwrap();
+    let bitboard = BitBoard::from_board(&board);
+
+    assert_eq!(7, bitboard.get_edge_count());
+    assert_eq!(2, bitboard.get_square_count());
+    assert_eq!(3, bitboard.get_free_edges().len());
+    assert!(bitboard.is_drawn(board.get_edge_index(&Edge::vertical(0, 1))));
+    assert_eq!(Player::Player2, bitboard.get_owner(0));
+    assert_eq!(Player::Dummy, bitboard.get_owner(1));
+    assert_eq!(1, bitboard.get_score(Player::Player2));
+    assert_eq!(Player::Player2, bitboard.get_current_player());
+    assert_eq!(Square::new(0, 1), bitboard.get_square(1));
+    assert_eq!(1, bitboard.get_drawn_sides(1));
+}
+
+#[test]
+fn test_make_and_unmake_move() {
+    let board = Board::new(3, 2).unwrap();
+    let mut bitboard = BitBoard::from_board(&board);
+    let middle = board.get_edge_index(&Edge::vertical(0, 1));
+
+    for edge in board.get_edges() {
+        if edge != Edge::vertical(0, 1) {
+            let index = board.get_edge_index(&edge);
+            assert_eq!(0, bitboard.make_move(index));
+        }
+    }
+
+    let before = bitboard.clone();
+    let current = bitboard.get_current_player();
+    assert_eq!(2, bitboard.get_captures(middle));
+    assert_eq!(2, bitboard.make_move(middle));
+    assert!(bitboard.is_complete());
+    assert_eq!(2, bitboard.get_score(current));
+    assert_eq!(current, bitboard.get_current_player());
+
+    bitboard.unmake_move();
+    assert_eq!(before.get_drawn(), bitboard.get_drawn());
+    assert_eq!(0, bitboard.get_score(current));
+    assert_eq!(current, bitboard.get_current_player());
+    assert_eq!(6, bitboard.get_depth());
+
+    while bitboard.get_depth() > 0 {
+        bitboard.unmake_move();
+    }
+    assert_eq!(7, bitboard.get_free_edges().len());
+    assert_eq!(Player::Player1, bitboard.get_current_player());
+}
+
+#[test]
+fn test_search_takes_box() {
+    let board = Board::from_position_str("4x4|h0,0=1 h1,0=2 v0,0=1 h3,2=1 v2,2=2||2").unwrap();
+    let result = MinMax::alphabeta(&board, 3, i32::MIN, i32::MAX, true);
+
+    assert!(result.0.contains_edge(&Edge::vertical(0, 1)));
+    assert_eq!(1, result.0.get_marked_by_player_2().len());
+    assert_eq!(1, result.0.get_history().len());
+}
+
+#[test]
+fn test_search_large_board() {
+    let board = Board::new(6, 6).unwrap();
+    let start = Instant::now();
+    let result = MinMax::alphabeta(&board, 3, i32::MIN, i32::MAX, true);
+
+    assert_eq!(1, result.0.get_lines().len());
+    assert!(start.elapsed().as_secs() < 10);
+}
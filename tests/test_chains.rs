@@ -0,0 +1,121 @@
+use dotsnboxes::bitboard::BitBoard;
+use dotsnboxes::chains::{self, ChainAnalysis, ComponentKind};
+use dotsnboxes::engine::{ChainEngine, Engine};
+use dotsnboxes::entities::{Board, Edge, Player};
+
+fn get_bitboard(position: &str) -> BitBoard {
+    BitBoard::from_board(&Board::from_position_str(position).unwrap())
+}
+
+// A row of boxes with the top and bottom of each drawn.
+fn get_row_position(boxes: usize) -> String {
+    let lines: Vec<String> = (0..boxes)
+        .flat_map(|col| vec![format!("h0,{}=1", col), format!("h1,{}=2", col)])
+        .collect();
+
+    format!("{}x2|{}||1", boxes + 1, lines.join(" "))
+}
+
+// A 4x2 grid of boxes with all horizontal lines drawn. The top row has two
+// boxes taken on the left and is being captured from there: one capturable box
+// and one more behind it against the border.
+fn get_capturing_position(bottom_taken: bool) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for row in 0..3 {
+        for col in 0..4 {
+            lines.push(format!("h{},{}=1", row, col));
+        }
+    }
+    for col in 0..3 {
+        lines.push(format!("v0,{}=2", col));
+    }
+
+    let mut squares = vec![String::from("0,0=2"), String::from("0,1=2")];
+    if bottom_taken {
+        for col in 0..5 {
+            lines.push(format!("v1,{}=1", col));
+        }
+        for col in 0..4 {
+            squares.push(format!("1,{}=1", col));
+        }
+    }
+
+    format!("5x3|{}|{}|2", lines.join(" "), squares.join(" "))
+}
+
+#[test]
+fn test_finds_chain() {
+    let bitboard = get_bitboard(&get_row_position(3));
+    let analysis = ChainAnalysis::new(&bitboard);
+
+    assert_eq!(analysis.get_components().len(), 1);
+    let chain = &analysis.get_components()[0];
+    assert_eq!(chain.get_kind(), ComponentKind::Chain);
+    let mut squares = chain.get_squares().to_vec();
+    squares.sort();
+    assert_eq!(squares, vec![0, 1, 2]);
+    assert_eq!(chain.get_edges().len(), 4);
+    assert!(chain.is_long());
+    assert_eq!(analysis.get_long_chains(), 1);
+    assert_eq!(analysis.get_control_value(), 3);
+}
+
+#[test]
+fn test_finds_loop() {
+    let bitboard = get_bitboard("3x3|h0,0=1 h0,1=2 h2,0=1 h2,1=2 v0,0=1 v1,0=2 v0,2=1 v1,2=2||1");
+    let analysis = ChainAnalysis::new(&bitboard);
+
+    assert_eq!(analysis.get_components().len(), 1);
+    let ring = &analysis.get_components()[0];
+    assert_eq!(ring.get_kind(), ComponentKind::Loop);
+    assert_eq!(ring.get_size(), 4);
+    assert_eq!(ring.get_edges().len(), 4);
+    assert_eq!(analysis.get_loops(), 1);
+    assert_eq!(analysis.get_long_chains(), 0);
+    assert_eq!(analysis.get_control_value(), 4);
+}
+
+#[test]
+fn test_safe_moves() {
+    let empty = get_bitboard("3x3|||1");
+    assert_eq!(chains::get_safe_moves(&empty).len(), 12);
+
+    let row = get_bitboard(&get_row_position(3));
+    assert!(chains::get_safe_moves(&row).is_empty());
+}
+
+#[test]
+fn test_opens_long_chain_at_end() {
+    let bitboard = get_bitboard(&get_row_position(3));
+    let moves = chains::get_policy_moves(&bitboard);
+
+    assert_eq!(moves.len(), 1);
+    let edge = bitboard.get_edge(moves[0]);
+    assert!(edge == Edge::vertical(0, 0) || edge == Edge::vertical(0, 3));
+}
+
+#[test]
+fn test_splits_short_chain() {
+    let board = Board::from_position_str(&get_row_position(2)).unwrap();
+    let mut engine = ChainEngine::with_seed(1);
+
+    assert_eq!(engine.choose_move(&board, Player::Player1).unwrap(), Edge::vertical(0, 1));
+}
+
+#[test]
+fn test_declines_last_two_boxes() {
+    let bitboard = get_bitboard(&get_capturing_position(false));
+    let moves = chains::get_policy_moves(&bitboard);
+
+    assert_eq!(moves.len(), 1);
+    assert_eq!(bitboard.get_edge(moves[0]), Edge::vertical(0, 4));
+}
+
+#[test]
+fn test_takes_all_when_nothing_is_left() {
+    let bitboard = get_bitboard(&get_capturing_position(true));
+    let moves = chains::get_policy_moves(&bitboard);
+
+    assert_eq!(moves.len(), 1);
+    assert_eq!(bitboard.get_edge(moves[0]), Edge::vertical(0, 3));
+}
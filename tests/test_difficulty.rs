@@ -0,0 +1,86 @@
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+mod common;
+
+use dotsnboxes::difficulty::{Difficulty, DifficultySettings};
+use dotsnboxes::engine::{BlunderEngine, Engine, EngineKind, GreedyEngine};
+use dotsnboxes::entities::{Board, Edge, MinMax, Player};
+
+#[test]
+fn test_levels_get_harder() {
+    let settings: Vec<DifficultySettings> = Difficulty::ALL.iter().map(|level| level.get_settings()).collect();
+
+    for pair in settings.windows(2) {
+        assert!(pair[0].get_blunder_probability() >= pair[1].get_blunder_probability());
+        assert!(pair[0].get_time_limit() <= pair[1].get_time_limit());
+    }
+
+    assert_eq!(Difficulty::Expert.get_settings().get_blunder_probability(), 0.0);
+    assert_eq!(Difficulty::Expert.next(), Difficulty::Easy);
+    assert_eq!(DifficultySettings::default().get_difficulty(), Difficulty::Medium);
+}
+
+#[test]
+fn test_difficulty_names() {
+    for level in Difficulty::ALL.iter() {
+        assert_eq!(level.to_string().parse::<Difficulty>().unwrap(), *level);
+    }
+
+    assert_eq!("hard".parse::<Difficulty>().unwrap(), Difficulty::Hard);
+    assert!("impossible".parse::<Difficulty>().is_err());
+}
+
+#[test]
+fn test_settings_create_engine() {
+    let mut settings = Difficulty::Medium.get_settings();
+    settings.set_engine(EngineKind::Greedy);
+    settings.set_blunder_probability(0.0);
+    settings.set_time_limit(Duration::from_millis(10));
+
+    let mut engine = settings.create_engine();
+    assert_eq!(engine.get_name(), "Greedy");
+    assert_eq!(engine.choose_move(&common::get_capture_board(), Player::Player1).unwrap(), Edge::vertical(1, 1));
+}
+
+#[test]
+fn test_blunder_engine() {
+    let board = common::get_capture_board();
+
+    let mut never = BlunderEngine::with_seed(Box::new(GreedyEngine::with_seed(1)), 0.0, 1);
+    for _ in 0..10 {
+        assert_eq!(never.choose_move(&board, Player::Player1).unwrap(), Edge::vertical(1, 1));
+    }
+
+    let mut always = BlunderEngine::with_seed(Box::new(GreedyEngine::with_seed(1)), 1.0, 1);
+    let blunders = (0..20)
+        .filter(|_| always.choose_move(&board, Player::Player1).unwrap() != Edge::vertical(1, 1))
+        .count();
+    assert!(blunders > 0);
+
+    let mut nan = BlunderEngine::with_seed(Box::new(GreedyEngine::with_seed(1)), f64::NAN, 1);
+    assert_eq!(nan.get_probability(), 0.0);
+    assert_eq!(nan.choose_move(&board, Player::Player1).unwrap(), Edge::vertical(1, 1));
+    assert_eq!(BlunderEngine::with_seed(Box::new(GreedyEngine::with_seed(1)), f64::INFINITY, 1).get_probability(), 0.0);
+}
+
+#[test]
+fn test_changing_level_keeps_engine() {
+    let mut settings = Difficulty::Easy.get_settings();
+    settings.set_engine(EngineKind::Mcts);
+
+    settings.set_difficulty(Difficulty::Hard);
+    assert_eq!(settings.get_difficulty(), Difficulty::Hard);
+    assert_eq!(settings.get_engine(), EngineKind::Mcts);
+    assert_eq!(settings.get_time_limit(), Difficulty::Hard.get_settings().get_time_limit());
+    assert_eq!(settings.get_blunder_probability(), 0.0);
+}
+
+#[test]
+fn test_minmax_depth_limit() {
+    let board = Board::new(4, 4).unwrap();
+    let (_, _, depth) = MinMax::iterative_deepening_with_stop(&board, Duration::from_secs(10), 2, &AtomicBool::new(false));
+
+    assert_eq!(depth, 2);
+    assert_eq!(MinMax::with_limits(Duration::from_millis(10), 2).get_max_depth(), 2);
+}
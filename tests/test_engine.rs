@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+mod common;
+
+use dotsnboxes::engine::{AvoidThirdSideEngine, Engine, EngineKind, GreedyEngine, RandomEngine};
+use dotsnboxes::entities::{Board, Edge, MinMax, Player};
+
+#[test]
+fn test_random_engine_picks_free_edge() {
+    let board = common::get_capture_board();
+    let mut engine = RandomEngine::with_seed(1);
+
+    for _ in 0..10 {
+        let edge = engine.choose_move(&board, Player::Player1).unwrap();
+        assert!(board.get_free_edges().contains(&edge));
+    }
+}
+
+#[test]
+fn test_greedy_engine_takes_box() {
+    let board = common::get_capture_board();
+    let mut engine = GreedyEngine::with_seed(1);
+
+    assert_eq!(engine.choose_move(&board, Player::Player1).unwrap(), Edge::vertical(1, 1));
+}
+
+#[test]
+fn test_avoid_third_side_engine_takes_box() {
+    let board = common::get_capture_board();
+    let mut engine = AvoidThirdSideEngine::with_seed(1);
+
+    assert_eq!(engine.choose_move(&board, Player::Player1).unwrap(), Edge::vertical(1, 1));
+}
+
+#[test]
+fn test_avoid_third_side_engine_avoids_third_side() {
+    let mut board = common::get_capture_board();
+    board.play(Edge::vertical(1, 1)).unwrap();
+    let mut engine = AvoidThirdSideEngine::with_seed(1);
+
+    for _ in 0..10 {
+        let edge = engine.choose_move(&board, Player::Player1).unwrap();
+        let mut next = board.clone();
+        next.play(edge).unwrap();
+
+        let third_sides = next.get_squares().iter()
+            .filter(|square| square.get_edges().iter().filter(|side| next.contains_edge(side)).count() == 3)
+            .count();
+        assert_eq!(third_sides, 0);
+    }
+}
+
+#[test]
+fn test_minmax_engine_picks_free_edge() {
+    let board = common::get_capture_board();
+    let mut engine = MinMax::with_time_limit(Duration::from_millis(50));
+
+    let edge = engine.choose_move(&board, Player::Player1).unwrap();
+    assert!(board.get_free_edges().contains(&edge));
+}
+
+#[test]
+fn test_engines_refuse_finished_game() {
+    let mut board = Board::new(2, 2).unwrap();
+    for edge in board.get_edges() {
+        board.play(edge).unwrap();
+    }
+
+    for kind in EngineKind::ALL.iter() {
+        assert!(kind.create().choose_move(&board, Player::Player1).is_err());
+    }
+}
+
+#[test]
+fn test_engine_kind_names() {
+    for kind in EngineKind::ALL.iter() {
+        assert_eq!(kind.to_string().parse::<EngineKind>().unwrap(), *kind);
+    }
+
+    assert_eq!(EngineKind::Mcts.next(), EngineKind::MinMax);
+    assert!("deep-blue".parse::<EngineKind>().is_err());
+}
@@ -1,4 +1,5 @@
-use dotsnboxes::entities::{MathOperations, Player, Line, Square, Board, DELTA};
+use dotsnboxes::entities::{MathOperations, Player, Edge, Line, Square, Board, BoardView, MinMax, DELTA};
+use dotsnboxes::error::Error;
 
 #[test]
 fn test_basic_distance() {
@@ -91,83 +92,370 @@ fn test_are_not_on_the_same_line() {
 
 #[test]
 fn test_line_equallity() {
-    let line1 = Line::new(6.0, 2.0, 3.0, 4.0, Player::Dummy);
-    let line2 = Line::new(3.0, 4.0, 6.0, 2.0, Player::Dummy);
-
+    let line1 = Line::new(Edge::horizontal(1, 0), Player::Player1);
+    let line2 = Line::new(Edge::horizontal(1, 0), Player::Player2);
+    let line3 = Line::new(Edge::vertical(1, 0), Player::Player1);
 
     assert!(line1 == line2);
+    assert!(line1 != line3);
 }
 
 #[test]
-fn test_get_smallest() {
-    let line1 = Line::new(6.0, 2.0, 3.0, 4.0, Player::Dummy);
-    let line2 = Line::new(21.0, 11.0, 3.0, 9.0, Player::Dummy);
-    let line3 = Line::new(2.0, 2.0, 4.0, 3.0, Player::Dummy);
-    let line4 = Line::new(8.0, 18.0, 3.0, 13.0, Player::Dummy);
-
-    let square = Square::new(line1, line2, line3, line4);
-    let actual = square.get_smallest();
-
-    assert!(eq_float(2.0, actual.0));
-    assert!(eq_float(2.0, actual.1));
+fn test_square_edges() {
+    let square = Square::new(1, 2);
+    let edges = square.get_edges();
+
+    assert_eq!(Edge::horizontal(1, 2), edges[0]);
+    assert_eq!(Edge::vertical(1, 3), edges[1]);
+    assert_eq!(Edge::horizontal(2, 2), edges[2]);
+    assert_eq!(Edge::vertical(1, 2), edges[3]);
 }
 
 #[test]
-fn test_square_equallity() {
-    let line1 = Line::new(6.0, 2.0, 3.0, 4.0, Player::Dummy);
-    let line2 = Line::new(21.0, 11.0, 3.0, 9.0, Player::Dummy);
-    let line3 = Line::new(2.0, 2.0, 4.0, 3.0, Player::Dummy);
-    let line4 = Line::new(8.0, 18.0, 3.0, 13.0, Player::Dummy);
-
-    let square1 = Square::new(line1, line2, line3, line4);
-    let square2 = Square::new(line3, line1, line2, line4);
-
-    assert!(square1 == square2);
+fn test_board_edges() {
+    let board = Board::new(3, 3).unwrap();
+    let edges = board.get_edges();
+
+    assert_eq!(12, edges.len());
+    assert_eq!(4, board.get_squares().len());
+    assert_eq!(Edge::horizontal(0, 0), edges[0]);
+    assert_eq!(Edge::vertical(1, 2), edges[11]);
 }
 
 #[test]
 fn test_board_add_line() {
-    let mut board = Board::new(3.0, 3.0, 600.0, 600.0, 50.0, 50.0);
-    let line = Line::new(6.0, 2.0, 3.0, 4.0, Player::Dummy);
-    board.add_line(line);
+    let mut board = Board::new(3, 3).unwrap();
+    let edge = Edge::vertical(0, 1);
+    board.add_line(Line::new(edge, Player::Player1)).unwrap();
 
-    assert!(board.contains_line(&line));
+    assert!(board.contains_edge(&edge));
+    assert!(!board.contains_edge(&Edge::horizontal(0, 1)));
+}
+
+#[test]
+fn test_line_points() {
+    let board = Board::new(3, 3).unwrap();
+    let view = BoardView::new(&board, 600.0, 600.0, 50.0, 50.0);
+    let points = view.get_line_points(&Edge::vertical(0, 1));
+
+    assert!(eq_float(300.0, (points.0).0));
+    assert!(eq_float(50.0, (points.0).1));
+    assert!(eq_float(300.0, (points.1).0));
+    assert!(eq_float(300.0, (points.1).1));
 }
 
 #[test]
 fn test_temp_line_positioning() {
-    let expected = Line::new(300.0, 300.0, 300.0, 50.0, Player::Player1);
+    let expected = Edge::vertical(0, 1);
 
-    let mut board = Board::new(3.0, 3.0, 600.0, 600.0, 50.0, 50.0);
-    board.update_line(Player::Player1, 300.0, 200.0);
-    let temp_line = board.get_temp_line();
+    let board = Board::new(3, 3).unwrap();
+    let mut view = BoardView::new(&board, 600.0, 600.0, 50.0, 50.0);
+    view.update_line(&board, 300.0, 200.0);
+    let temp_line = view.get_temp_line();
 
-    assert_eq!(expected, temp_line);
+    assert_eq!(Some(expected), temp_line);
 }
 
 #[test]
 fn test_update_squares() {
-    let line1 = Line::new(50.0, 50.0, 50.0, 300.0, Player::Player1);
-    let line2 = Line::new(300.0, 50.0, 50.0, 50.0, Player::Player1);
-    let line3 = Line::new(50.0, 300.0, 300.0, 300.0, Player::Player1);
-    let line4 = Line::new(300.0, 300.0, 300.0, 50.0, Player::Player1);
-
-    let mut board = Board::new(3.0, 3.0, 600.0, 600.0, 50.0, 50.0);
+    let mut board = Board::new(3, 3).unwrap();
+    let edges = Square::new(0, 0).get_edges();
 
-    board.add_line(line1);
-    board.add_line(line2);
-    board.add_line(line3);
-    board.add_line(line4);
+    for edge in edges.iter().take(3) {
+        board.add_line(Line::new(*edge, Player::Player1)).unwrap();
+        assert!(board.update_squares(edge, Player::Player1).is_empty());
+    }
 
     assert_eq!(0, board.get_marked_by_player_1().len());
     assert_eq!(0, board.get_marked_by_player_2().len());
 
-    board.update_squares(Player::Player1);
+    board.add_line(Line::new(edges[3], Player::Player1)).unwrap();
+    let completed = board.update_squares(&edges[3], Player::Player1);
 
+    assert_eq!(vec![Square::new(0, 0)], completed);
     assert_eq!(1, board.get_marked_by_player_1().len());
     assert_eq!(0, board.get_marked_by_player_2().len());
 }
 
+#[test]
+fn test_adjacent_squares() {
+    let board = Board::new(4, 4).unwrap();
+
+    assert_eq!(vec![Square::new(0, 1)], board.get_adjacent_squares(&Edge::horizontal(0, 1)));
+    assert_eq!(vec![Square::new(2, 0)], board.get_adjacent_squares(&Edge::horizontal(3, 0)));
+    assert_eq!(vec![Square::new(1, 0), Square::new(1, 1)], board.get_adjacent_squares(&Edge::vertical(1, 1)));
+    assert_eq!(vec![Square::new(2, 2)], board.get_adjacent_squares(&Edge::vertical(2, 3)));
+}
+
+#[test]
+fn test_update_squares_completes_two() {
+    let mut board = Board::new(3, 2).unwrap();
+    let middle = Edge::vertical(0, 1);
+
+    for edge in board.get_edges() {
+        if edge != middle {
+            board.add_line(Line::new(edge, Player::Player1)).unwrap();
+            board.update_squares(&edge, Player::Player1);
+        }
+    }
+
+    board.add_line(Line::new(middle, Player::Player2)).unwrap();
+    let completed = board.update_squares(&middle, Player::Player2);
+
+    assert_eq!(2, completed.len());
+    assert_eq!(0, board.get_marked_by_player_1().len());
+    assert_eq!(2, board.get_marked_by_player_2().len());
+    assert!(board.is_complete());
+}
+
+#[test]
+fn test_update_squares_large_board() {
+    let mut board = Board::new(11, 11).unwrap();
+
+    for edge in board.get_edges() {
+        board.add_line(Line::new(edge, Player::Player1)).unwrap();
+        board.update_squares(&edge, Player::Player1);
+    }
+
+    assert_eq!(100, board.get_marked_by_player_1().len());
+    assert!(board.is_complete());
+}
+
+#[test]
+fn test_rectangular_boards() {
+    for &(rows, cols) in &[(5, 3), (3, 5), (2, 9), (9, 2), (1, 4)] {
+        let mut board = Board::new(cols + 1, rows + 1).unwrap();
+        let edges = board.get_edges();
+
+        assert_eq!(rows, board.get_rows());
+        assert_eq!(cols, board.get_cols());
+        assert_eq!(rows * cols, board.get_squares().len());
+        assert_eq!(rows * (cols + 1) + cols * (rows + 1), edges.len());
+
+        for (i, edge) in edges.iter().enumerate() {
+            assert_eq!(i, board.get_edge_index(edge));
+        }
+
+        for edge in edges {
+            board.add_line(Line::new(edge, Player::Player2)).unwrap();
+            board.update_squares(&edge, Player::Player2);
+        }
+
+        assert_eq!(rows * cols, board.get_marked_by_player_2().len());
+        assert!(board.is_complete());
+    }
+}
+
+#[test]
+fn test_rectangular_view() {
+    let board = Board::new(4, 2).unwrap();
+    let mut view = BoardView::new(&board, 600.0, 600.0, 60.0, 60.0);
+
+    assert!(eq_float(160.0, view.get_step_x()));
+    assert!(eq_float(160.0, view.get_step_y()));
+
+    let top_left = view.get_point(0, 0);
+    let bottom_right = view.get_point(1, 3);
+    assert!(eq_float(60.0, top_left.0));
+    assert!(eq_float(220.0, top_left.1));
+    assert!(eq_float(540.0, bottom_right.0));
+    assert!(eq_float(380.0, bottom_right.1));
+
+    view.update_line(&board, 500.0, 300.0);
+    assert_eq!(Some(Edge::vertical(0, 3)), view.get_temp_line());
+
+    view.update_line(&board, 300.0, 370.0);
+    assert_eq!(Some(Edge::horizontal(1, 1)), view.get_temp_line());
+}
+
+#[test]
+fn test_minmax_rectangular_board() {
+    let mut board = Board::new(4, 2).unwrap();
+    let missing = Edge::vertical(0, 3);
+
+    for edge in board.get_edges() {
+        if edge != missing {
+            board.add_line(Line::new(edge, Player::Player1)).unwrap();
+            board.update_squares(&edge, Player::Player1);
+        }
+    }
+
+    let result = MinMax::alphabeta(&board, 2, i32::MIN, i32::MAX, true);
+
+    assert!(result.0.contains_edge(&missing));
+    assert_eq!(1, result.0.get_marked_by_player_2().len());
+}
+
+#[test]
+fn test_invalid_dimensions() {
+    assert!(matches!(Board::new(1, 3), Err(Error::InvalidDimensions(1, 3))));
+    assert!(matches!(Board::new(4, 0), Err(Error::InvalidDimensions(4, 0))));
+}
+
+#[test]
+fn test_add_line_errors() {
+    let mut board = Board::new(2, 2).unwrap();
+
+    assert!(matches!(board.add_line(Line::new(Edge::horizontal(0, 1), Player::Player1)), Err(Error::IllegalMove(_))));
+    assert!(matches!(board.add_line(Line::new(Edge::vertical(1, 0), Player::Player1)), Err(Error::IllegalMove(_))));
+
+    board.add_line(Line::new(Edge::horizontal(0, 0), Player::Player1)).unwrap();
+    assert!(matches!(board.add_line(Line::new(Edge::horizontal(0, 0), Player::Player2)), Err(Error::LineAlreadyDrawn(_))));
+
+    for edge in board.get_edges().into_iter().skip(1) {
+        board.add_line(Line::new(edge, Player::Player1)).unwrap();
+        board.update_squares(&edge, Player::Player1);
+    }
+
+    assert!(board.is_complete());
+    assert!(matches!(board.add_line(Line::new(Edge::horizontal(0, 0), Player::Player2)), Err(Error::GameOver)));
+}
+
+#[test]
+fn test_play_switches_turns() {
+    let mut board = Board::new(3, 3).unwrap();
+    assert_eq!(Player::Player1, board.get_current_player());
+
+    let outcome = board.play(Edge::horizontal(0, 0)).unwrap();
+    assert_eq!(Player::Player1, outcome.get_player());
+    assert_eq!(Edge::horizontal(0, 0), outcome.get_edge());
+    assert!(outcome.get_captured().is_empty());
+    assert!(!outcome.moves_again());
+    assert_eq!(Player::Player2, board.get_current_player());
+
+    assert!(matches!(board.play(Edge::horizontal(0, 0)), Err(Error::LineAlreadyDrawn(_))));
+    assert_eq!(Player::Player2, board.get_current_player());
+}
+
+#[test]
+fn test_play_capture_keeps_turn() {
+    let mut board = Board::new(3, 2).unwrap();
+
+    board.play(Edge::horizontal(0, 0)).unwrap();
+    board.play(Edge::horizontal(1, 0)).unwrap();
+    board.play(Edge::vertical(0, 0)).unwrap();
+    assert_eq!(Player::Player2, board.get_current_player());
+
+    let outcome = board.play(Edge::vertical(0, 1)).unwrap();
+    assert_eq!(Player::Player2, outcome.get_player());
+    assert_eq!(&[Square::new(0, 0)], outcome.get_captured());
+    assert!(outcome.moves_again());
+    assert_eq!(Player::Player2, board.get_current_player());
+    assert_eq!(1, board.get_marked_by_player_2().len());
+}
+
+#[test]
+fn test_undo_redo() {
+    let mut board = Board::new(3, 2).unwrap();
+
+    board.play(Edge::horizontal(0, 0)).unwrap();
+    board.play(Edge::horizontal(1, 0)).unwrap();
+    board.play(Edge::vertical(0, 0)).unwrap();
+    board.play(Edge::vertical(0, 1)).unwrap();
+    assert_eq!(1, board.get_marked_by_player_2().len());
+    assert_eq!(4, board.get_history().len());
+
+    let outcome = board.undo().unwrap();
+    assert_eq!(Edge::vertical(0, 1), outcome.get_edge());
+    assert!(!board.contains_edge(&Edge::vertical(0, 1)));
+    assert_eq!(0, board.get_marked_by_player_2().len());
+    assert_eq!(Player::Player2, board.get_current_player());
+
+    board.undo().unwrap();
+    assert_eq!(Player::Player1, board.get_current_player());
+    assert!(board.can_redo());
+
+    board.redo().unwrap();
+    let outcome = board.redo().unwrap();
+    assert!(outcome.moves_again());
+    assert_eq!(1, board.get_marked_by_player_2().len());
+    assert_eq!(Player::Player2, board.get_current_player());
+    assert!(!board.can_redo());
+}
+
+#[test]
+fn test_play_clears_redo() {
+    let mut board = Board::new(3, 3).unwrap();
+
+    board.play(Edge::horizontal(0, 0)).unwrap();
+    board.undo().unwrap();
+    assert!(board.can_redo());
+    assert!(!board.can_undo());
+
+    board.play(Edge::vertical(0, 0)).unwrap();
+    assert!(!board.can_redo());
+    assert!(board.redo().is_none());
+    assert_eq!(1, board.get_lines().len());
+}
+
+#[test]
+fn test_edge_notation() {
+    assert_eq!("h0,1", Edge::horizontal(0, 1).to_string());
+    assert_eq!("v12,3", Edge::vertical(12, 3).to_string());
+
+    assert_eq!(Edge::horizontal(3, 2), "h3,2".parse().unwrap());
+    assert_eq!(Edge::vertical(1, 4), "V1,4".parse().unwrap());
+    assert_eq!(Edge::vertical(1, 4), " v1, 4 ".parse().unwrap());
+
+    for invalid in &["", "x1,2", "h1", "h1,2,3", "h-1,2", "va,b"] {
+        assert!(matches!(invalid.parse::<Edge>(), Err(Error::InvalidNotation(_))));
+    }
+}
+
+#[test]
+fn test_position_str() {
+    let mut board = Board::new(3, 2).unwrap();
+    board.play(Edge::horizontal(0, 0)).unwrap();
+    board.play(Edge::horizontal(1, 0)).unwrap();
+    board.play(Edge::vertical(0, 0)).unwrap();
+    board.play(Edge::vertical(0, 1)).unwrap();
+
+    assert_eq!("3x2|h0,0=1 h1,0=2 v0,0=1 v0,1=2|0,0=2|2", board.to_position_str());
+    assert_eq!("4x3|||1", Board::new(4, 3).unwrap().to_position_str());
+}
+
+#[test]
+fn test_position_str_round_trip() {
+    let mut board = Board::new(4, 3).unwrap();
+    for edge in board.get_edges().into_iter().step_by(2) {
+        board.play(edge).unwrap();
+    }
+    board.play(Edge::horizontal(1, 0)).unwrap();
+    board.play(Edge::vertical(0, 0)).unwrap();
+    board.play(Edge::horizontal(1, 2)).unwrap();
+
+    let position = board.to_position_str();
+    let restored = Board::from_position_str(&position).unwrap();
+
+    assert_eq!(position, restored.to_position_str());
+    assert_eq!(board.get_current_player(), restored.get_current_player());
+    assert_eq!(board.get_marked_by_player_1().len(), restored.get_marked_by_player_1().len());
+    assert_eq!(board.get_marked_by_player_2().len(), restored.get_marked_by_player_2().len());
+    for edge in board.get_edges() {
+        assert_eq!(board.contains_edge(&edge), restored.contains_edge(&edge));
+    }
+}
+
+#[test]
+fn test_position_str_playable() {
+    let mut board = Board::from_position_str("3x2|h0,0=1 h1,0=2 v0,0=1|| 2").unwrap();
+
+    let outcome = board.play(Edge::vertical(0, 1)).unwrap();
+    assert!(outcome.moves_again());
+    assert_eq!(1, board.get_marked_by_player_2().len());
+}
+
+#[test]
+fn test_invalid_position_str() {
+    for position in &["3x2|||", "3x2||", "3|||1", "3x2|h0,0|1|1", "3x2|h0,0=3||1", "3x2|||x",
+            "3x2|h0,0=1||0,0=1|1", "3x2|h0,0=1 h1,0=2 v0,0=1 v0,1=2||1"] {
+        assert!(matches!(Board::from_position_str(position), Err(Error::InvalidPosition(_))), "{}", position);
+    }
+
+    assert!(matches!(Board::from_position_str("1x2|||1"), Err(Error::InvalidDimensions(1, 2))));
+    assert!(matches!(Board::from_position_str("3x2|h0,5=1||1"), Err(Error::IllegalMove(_))));
+}
+
 fn eq_float(a: f32, b: f32) -> bool {
     (a - b) < DELTA
 }
\ No newline at end of file
@@ -0,0 +1,47 @@
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use dotsnboxes::entities::{Board, Edge};
+use dotsnboxes::hint::{Evaluation, Hint};
+
+#[test]
+fn test_exact_hint_late_in_game() {
+    let board = Board::from_position_str("3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2").unwrap();
+
+    let hint = Hint::find(&board, Duration::from_millis(100), &AtomicBool::new(false)).unwrap();
+
+    assert_eq!(hint.get_edge(), Edge::vertical(0, 1));
+    assert_eq!(hint.get_evaluation(), Evaluation::Exact(2));
+    assert_eq!(hint.to_string(), "v0,1 wins by 2");
+}
+
+#[test]
+fn test_estimated_hint_early_in_game() {
+    let board = Board::new(5, 5).unwrap();
+
+    let hint = Hint::find(&board, Duration::from_millis(100), &AtomicBool::new(false)).unwrap();
+
+    assert!(board.get_free_edges().contains(&hint.get_edge()));
+    match hint.get_evaluation() {
+        Evaluation::Estimate { depth, .. } => assert!(depth >= 1),
+        Evaluation::Exact(_) => panic!("the board is too large for the solver"),
+    }
+}
+
+#[test]
+fn test_no_hint_for_finished_game() {
+    let mut board = Board::new(2, 2).unwrap();
+    for edge in board.get_edges() {
+        board.play(edge).unwrap();
+    }
+
+    assert!(Hint::find(&board, Duration::from_millis(100), &AtomicBool::new(false)).is_err());
+}
+
+#[test]
+fn test_evaluation_display() {
+    assert_eq!(Evaluation::Exact(-3).to_string(), "loses by 3");
+    assert_eq!(Evaluation::Exact(0).to_string(), "draws");
+    assert_eq!(Evaluation::Estimate { value: 2, depth: 4 }.to_string(), "estimated margin +2 at depth 4");
+    assert_eq!(Evaluation::Estimate { value: -1, depth: 3 }.to_string(), "estimated margin -1 at depth 3");
+}
@@ -0,0 +1,59 @@
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use dotsnboxes::engine::Engine;
+use dotsnboxes::entities::{Board, Edge, Player};
+use dotsnboxes::mcts::{Budget, Mcts, DEFAULT_EXPLORATION};
+
+#[test]
+fn test_respects_iteration_budget() {
+    let board = Board::new(3, 3).unwrap();
+    let mut mcts = Mcts::new(DEFAULT_EXPLORATION, Budget::Iterations(200), 1);
+
+    let edge = mcts.choose_move(&board, Player::Player1).unwrap();
+
+    assert_eq!(mcts.get_iterations(), 200);
+    assert!(board.get_free_edges().contains(&edge));
+}
+
+#[test]
+fn test_respects_time_budget() {
+    let board = Board::new(6, 6).unwrap();
+    let mut mcts = Mcts::new(DEFAULT_EXPLORATION, Budget::Time(Duration::from_millis(100)), 1);
+
+    let start = Instant::now();
+    mcts.choose_move(&board, Player::Player1).unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(mcts.get_iterations() > 0);
+}
+
+#[test]
+fn test_same_seed_same_move() {
+    let board = Board::from_position_str("4x4|h0,0=1 h1,1=2 v0,0=1 v1,2=2 h3,2=1||1").unwrap();
+
+    let first = Mcts::new(DEFAULT_EXPLORATION, Budget::Iterations(300), 42).choose_move(&board, Player::Player1).unwrap();
+    let second = Mcts::new(DEFAULT_EXPLORATION, Budget::Iterations(300), 42).choose_move(&board, Player::Player1).unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_takes_box_and_moves_again() {
+    // taking the box leaves player 2 to move again with one safe line left
+    let board = Board::from_position_str("3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2").unwrap();
+    let mut mcts = Mcts::new(DEFAULT_EXPLORATION, Budget::Iterations(500), 7);
+
+    assert_eq!(mcts.choose_move(&board, Player::Player2).unwrap(), Edge::vertical(0, 1));
+}
+
+#[test]
+fn test_stopped_search_still_moves() {
+    let board = Board::new(3, 3).unwrap();
+    let mut mcts = Mcts::new(DEFAULT_EXPLORATION, Budget::Iterations(1000), 1);
+
+    let edge = mcts.choose_move_with_stop(&board, Player::Player1, &AtomicBool::new(true)).unwrap();
+
+    assert_eq!(mcts.get_iterations(), 0);
+    assert!(board.get_free_edges().contains(&edge));
+}
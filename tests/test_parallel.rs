@@ -0,0 +1,122 @@
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use dotsnboxes::bitboard::BitBoard;
+use dotsnboxes::engine::Engine;
+use dotsnboxes::entities::{Board, Edge, MinMax, Player};
+use dotsnboxes::parallel::ParallelSearch;
+use dotsnboxes::search::SearchContext;
+use dotsnboxes::transposition::{Bound, Entry, SharedTable, TranspositionTable};
+
+fn serial_search(board: &Board, depth: u8) -> (Option<usize>, i32) {
+    let mut bitboard = BitBoard::from_board(board);
+    let mut table = TranspositionTable::default();
+    let is_max = board.get_current_player() == Player::Player2;
+    MinMax::search(&mut bitboard, &mut SearchContext::new(&mut table, None), depth, i32::MIN, i32::MAX, is_max)
+}
+
+#[test]
+fn test_matches_serial_search() {
+    let positions = ["3x3|||1", "4x3|h0,0=1 v1,1=2 h2,2=1||2", "3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2"];
+
+    for position in positions.iter() {
+        let board = Board::from_position_str(position).unwrap();
+        let bitboard = BitBoard::from_board(&board);
+
+        for depth in 1..=4 {
+            let serial = serial_search(&board, depth);
+
+            // a single thread searches exactly like the serial search
+            let mut search = ParallelSearch::new(1);
+            let found = search.search(&bitboard, depth, None, &AtomicBool::new(false));
+            assert_eq!(found, Some(serial), "{} at depth {}", position, depth);
+
+            // more threads may choose another move, but one just as good
+            let mut search = ParallelSearch::new(3);
+            let (best, value) = search.search(&bitboard, depth, None, &AtomicBool::new(false)).unwrap();
+            assert_eq!(value, serial.1, "{} at depth {}", position, depth);
+            assert!(search.get_nodes() > 0);
+
+            let mut child = board.clone();
+            child.play(bitboard.get_edge(best.unwrap())).unwrap();
+            let (_, child_value) = serial_search(&child, depth - 1);
+            assert_eq!(child_value, value, "{} at depth {}", position, depth);
+        }
+    }
+}
+
+#[test]
+fn test_root_is_stored() {
+    let board = Board::new(3, 3).unwrap();
+    let bitboard = BitBoard::from_board(&board);
+    let mut search = ParallelSearch::new(2);
+
+    let (best, value) = search.search(&bitboard, 3, None, &AtomicBool::new(false)).unwrap();
+    let table = search.get_table();
+    assert!(table.get_stores() > 0);
+    assert!(table.get_hits() <= table.get_probes());
+
+    let probes = table.get_probes();
+    let entry = table.probe(bitboard.get_hash()).unwrap();
+    assert_eq!(table.get_probes(), probes + 1);
+    assert!(table.get_hit_rate() > 0.0);
+    assert_eq!((entry.get_depth(), entry.get_value(), entry.get_bound(), entry.get_best()), (3, value, Bound::Exact, best));
+}
+
+#[test]
+fn test_stopped_search_gives_nothing() {
+    let board = Board::new(6, 6).unwrap();
+    let mut search = ParallelSearch::new(2);
+
+    let found = search.search(&BitBoard::from_board(&board), 8, Some(Instant::now()), &AtomicBool::new(false));
+    assert_eq!(found, None);
+
+    let found = search.search(&BitBoard::from_board(&board), 8, None, &AtomicBool::new(true));
+    assert_eq!(found, None);
+}
+
+#[test]
+fn test_iterative_deepening_takes_box() {
+    let board = Board::from_position_str("3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2").unwrap();
+    let mut search = ParallelSearch::new(2);
+
+    let (child, value, depth) = search.iterative_deepening(&board, Duration::from_secs(10), u8::MAX,
+        &AtomicBool::new(false));
+
+    assert_eq!(depth, 2);
+    assert_eq!(value, 2);
+    assert!(child.contains_edge(&Edge::vertical(0, 1)));
+}
+
+#[test]
+fn test_threaded_minmax_engine() {
+    let board = Board::new(4, 4).unwrap();
+    let mut minmax = MinMax::with_limits(Duration::from_millis(100), 3);
+    minmax.set_threads(4);
+
+    assert_eq!(minmax.get_threads(), 4);
+    let edge = minmax.choose_move(&board, Player::Player1).unwrap();
+    assert!(board.get_free_edges().contains(&edge));
+}
+
+#[test]
+fn test_shared_table_round_trip() {
+    let table = SharedTable::new(64);
+    assert!(table.probe(7).is_none());
+
+    table.store(Entry::new(7, 3, -12, Bound::Lower, Some(40)));
+    let entry = table.probe(7).unwrap();
+    assert_eq!((entry.get_depth(), entry.get_value(), entry.get_bound(), entry.get_best()), (3, -12, Bound::Lower, Some(40)));
+
+    // a shallower search of the same position does not replace a deeper one
+    table.store(Entry::new(7, 2, 5, Bound::Exact, None));
+    assert_eq!(table.probe(7).unwrap().get_value(), -12);
+
+    table.store(Entry::new(7 + 64, 1, 5, Bound::Upper, None));
+    assert!(table.probe(7).is_none());
+    assert_eq!(table.probe(7 + 64).unwrap().get_best(), None);
+
+    table.clear();
+    assert_eq!(table.get_probes(), 0);
+    assert!(table.probe(7 + 64).is_none());
+}
@@ -0,0 +1,112 @@
+use std::env;
+use std::fs;
+
+use dotsnboxes::difficulty::Difficulty;
+use dotsnboxes::entities::{Board, State};
+use dotsnboxes::error::Error;
+use dotsnboxes::ratings::{get_expected_score, Ratings, INITIAL_RATING, K_FACTOR};
+use dotsnboxes::record::GameRecord;
+use dotsnboxes::tournament::Tournament;
+
+#[test]
+fn test_expected_score() {
+    assert!((get_expected_score(1500.0, 1500.0) - 0.5).abs() < 1e-9);
+    assert!((get_expected_score(1900.0, 1500.0) - 10.0 / 11.0).abs() < 1e-9);
+    assert!((get_expected_score(1500.0, 1900.0) + get_expected_score(1900.0, 1500.0) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_game_moves_both_ratings() {
+    let mut ratings = Ratings::new();
+    ratings.add_game("Alice", "Bob", 3, 1);
+
+    assert!((ratings.get_rating("Alice") - (INITIAL_RATING + K_FACTOR / 2.0)).abs() < 1e-9);
+    assert!((ratings.get_rating("Bob") - (INITIAL_RATING - K_FACTOR / 2.0)).abs() < 1e-9);
+    assert_eq!(ratings.get("Alice").unwrap().get_wins(), 1);
+    assert_eq!(ratings.get("Bob").unwrap().get_losses(), 1);
+
+    ratings.add_game("Alice", "Bob", 2, 2);
+    assert_eq!(ratings.get("Bob").unwrap().get_draws(), 1);
+    assert!(ratings.get_rating("Bob") > INITIAL_RATING - K_FACTOR / 2.0);
+
+    let players = ratings.get_players();
+    assert_eq!(players[0].0, "Alice");
+    assert_eq!(players[1].0, "Bob");
+}
+
+#[test]
+fn test_only_finished_records_are_rated() {
+    let mut ratings = Ratings::new();
+    let mut board = Board::new(2, 2).unwrap();
+
+    let record = GameRecord::new(&board, State::TwoPlayers, String::from("Alice"), String::from("Bob"));
+    assert!(ratings.add_record(&record).is_err());
+
+    for edge in board.get_edges() {
+        board.play(edge).unwrap();
+    }
+    let record = GameRecord::new(&board, State::TwoPlayers, String::from("Alice"), String::from("Bob"));
+    ratings.add_record(&record).unwrap();
+    assert_eq!(ratings.get("Alice").unwrap().get_games(), 1);
+}
+
+#[test]
+fn test_suggested_difficulty_follows_rating() {
+    let ratings: Ratings = "Alice\t1120.0\t0\t0\t5\nBob\t1990.0\t5\t0\t0\nCarol\t1620.0\t2\t1\t2\n".parse().unwrap();
+
+    assert_eq!(ratings.suggest_difficulty("Alice"), Difficulty::Easy);
+    assert_eq!(ratings.suggest_difficulty("Bob"), Difficulty::Expert);
+    assert_eq!(ratings.suggest_difficulty("Carol"), Difficulty::Hard);
+    assert_eq!(ratings.suggest_difficulty("Dave"), Difficulty::Medium);
+
+    // a level which has been played is compared by its own rating
+    let hard: Ratings = format!("Carol\t1620.0\t2\t1\t2\n{}\t1300.0\t3\t0\t3\n",
+        Ratings::get_level_name(Difficulty::Hard)).parse().unwrap();
+    assert_eq!(hard.suggest_difficulty("Carol"), Difficulty::Medium);
+}
+
+#[test]
+fn test_tournament_pairings_are_rated() {
+    let engines = vec!["chains".parse().unwrap(), "random".parse().unwrap()];
+    let results = Tournament::new(engines, 3, 3, 4).run().unwrap();
+
+    let mut ratings = Ratings::new();
+    ratings.add_pairing(&results[0]);
+
+    assert_eq!(ratings.get("chains").unwrap().get_games(), 4);
+    assert_eq!(ratings.get("random").unwrap().get_games(), 4);
+    assert!((ratings.get_rating("chains") + ratings.get_rating("random") - 2.0 * INITIAL_RATING).abs() < 1e-9);
+}
+
+#[test]
+fn test_save_and_load() {
+    let mut ratings = Ratings::new();
+    ratings.add_game("Alice Smith", &Ratings::get_level_name(Difficulty::Hard), 1, 3);
+
+    let path = env::temp_dir().join("dotsnboxes_test_ratings.txt");
+    ratings.save(&path).unwrap();
+    let loaded = Ratings::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.get_players().len(), 2);
+    let rating = loaded.get("Alice Smith").unwrap();
+    assert!((rating.get_value() - ratings.get_rating("Alice Smith")).abs() < 0.1);
+    assert_eq!(rating.get_losses(), 1);
+
+    assert!(matches!("Alice\t15x0\t1\t0\t0".parse::<Ratings>(), Err(Error::InvalidRatings(_))));
+    assert_eq!(Ratings::load_or_new(env::temp_dir().join("dotsnboxes_missing_ratings.txt")).unwrap(), Ratings::new());
+}
+
+#[test]
+fn test_names_survive_save_and_load() {
+    let mut ratings = Ratings::new();
+    ratings.add_game("Tab\tand\nnew line", "back\\slash\\t", 2, 1);
+
+    let loaded: Ratings = ratings.to_string().parse().unwrap();
+    assert_eq!(loaded.get_players().len(), 2);
+    assert_eq!(loaded.get("Tab\tand\nnew line").unwrap().get_wins(), 1);
+    assert_eq!(loaded.get("back\\slash\\t").unwrap().get_losses(), 1);
+    assert_eq!(ratings.to_string().lines().count(), 2);
+
+    assert!(matches!("bad\\x\t1500.0\t0\t0\t0".parse::<Ratings>(), Err(Error::InvalidRatings(_))));
+}
@@ -0,0 +1,112 @@
+use dotsnboxes::entities::{Board, Edge, Player, State};
+use dotsnboxes::error::Error;
+use dotsnboxes::record::GameRecord;
+
+fn sample_board() -> Board {
+    let mut board = Board::new(3, 2).unwrap();
+
+    board.play(Edge::horizontal(0, 0)).unwrap();
+    board.play(Edge::horizontal(1, 0)).unwrap();
+    board.play(Edge::vertical(0, 0)).unwrap();
+    board.play(Edge::vertical(0, 1)).unwrap();
+
+    board
+}
+
+#[test]
+fn test_record_from_board() {
+    let record = GameRecord::new(&sample_board(), State::TwoPlayers, String::from("Alice"), String::from("Bob"));
+
+    assert_eq!(3, record.get_width());
+    assert_eq!(2, record.get_height());
+    assert_eq!(State::TwoPlayers, record.get_mode());
+    assert_eq!("Alice", record.get_player_1());
+    assert_eq!("Bob", record.get_player_2());
+    assert_eq!(4, record.get_moves().len());
+    assert_eq!(Player::Player2, record.get_current_player());
+}
+
+#[test]
+fn test_record_round_trip() {
+    let record = GameRecord::from_board(&sample_board(), State::OnePlayer);
+    let parsed: GameRecord = record.to_string().parse().unwrap();
+
+    assert_eq!(record, parsed);
+
+    let board = parsed.to_board().unwrap();
+    assert_eq!(1, board.get_marked_by_player_2().len());
+    assert_eq!(Player::Player2, board.get_current_player());
+    assert!(board.contains_edge(&Edge::vertical(0, 1)));
+}
+
+#[test]
+fn test_record_save_and_load() {
+    let path = std::env::temp_dir().join("dotsnboxes_test_record.save");
+    let record = GameRecord::from_board(&sample_board(), State::TwoPlayers);
+
+    record.save(&path).unwrap();
+    let loaded = GameRecord::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(record, loaded);
+}
+
+#[test]
+fn test_record_text() {
+    let record = GameRecord::new(&sample_board(), State::TwoPlayers, String::from("Alice"), String::from("Bob"));
+    let expected = "[Player1 \"Alice\"]\n[Player2 \"Bob\"]\n[Size \"3x2\"]\n[Mode \"TwoPlayers\"]\n\
+                    [Turn \"Player2\"]\n[Result \"*\"]\n\nh0,0 h1,0 v0,0 v0,1\n";
+
+    assert_eq!(expected, record.to_string());
+}
+
+#[test]
+fn test_record_finished_game() {
+    let mut board = sample_board();
+    board.play(Edge::vertical(0, 2)).unwrap();
+    board.play(Edge::horizontal(0, 1)).unwrap();
+    board.play(Edge::horizontal(1, 1)).unwrap();
+
+    let record = GameRecord::from_board(&board, State::TwoPlayers);
+    assert_eq!(Some((0, 2)), record.get_result());
+    assert!(record.to_string().contains("[Result \"0-2\"]"));
+
+    let parsed: GameRecord = record.to_string().parse().unwrap();
+    assert_eq!(Some((0, 2)), parsed.get_result());
+    assert!(parsed.to_board().unwrap().is_complete());
+}
+
+#[test]
+fn test_record_invalid() {
+    assert!(matches!("[Size \"3x3\"]\n[Mode \"OnePlayer\"]".parse::<GameRecord>(), Err(Error::InvalidRecord(_))));
+    assert!(matches!("[Size \"3xa\"]".parse::<GameRecord>(), Err(Error::InvalidRecord(_))));
+    assert!(matches!("[Colour \"red\"]".parse::<GameRecord>(), Err(Error::InvalidRecord(_))));
+    assert!(matches!("[Size 3x3]".parse::<GameRecord>(), Err(Error::InvalidRecord(_))));
+    assert!(matches!("[Size \"3x3\"]\nd0,0".parse::<GameRecord>(), Err(Error::InvalidNotation(_))));
+
+    let headers = "[Size \"3x3\"]\n[Mode \"TwoPlayers\"]\n";
+
+    let record: GameRecord = format!("{}[Turn \"Player1\"]\nh0,0", headers).parse().unwrap();
+    assert!(matches!(record.to_board(), Err(Error::InvalidRecord(_))));
+
+    let record: GameRecord = format!("{}[Turn \"Player2\"]\nh0,0 h0,0", headers).parse().unwrap();
+    assert!(matches!(record.to_board(), Err(Error::LineAlreadyDrawn(_))));
+
+    let record: GameRecord = format!("{}[Turn \"Player2\"]\n[Result \"4-0\"]\nh0,0", headers).parse().unwrap();
+    assert!(matches!(record.to_board(), Err(Error::InvalidRecord(_))));
+}
+
+#[test]
+fn test_record_names_survive_round_trip() {
+    let record = GameRecord::new(&sample_board(), State::TwoPlayers, String::from("Say \"hi\"\n[Size \"9x9\"]"),
+        String::from("back\\slash\\n\r"));
+    let text = record.to_string();
+    let parsed: GameRecord = text.parse().unwrap();
+
+    assert_eq!(record, parsed);
+    assert_eq!("Say \"hi\"\n[Size \"9x9\"]", parsed.get_player_1());
+    assert_eq!(text.lines().count(), 8);
+
+    assert!(matches!("[Player1 \"bad\\x\"]".parse::<GameRecord>(), Err(Error::InvalidRecord(_))));
+    assert!(matches!("[Player1 \"a\"b\"]".parse::<GameRecord>(), Err(Error::InvalidRecord(_))));
+}
@@ -0,0 +1,175 @@
+use std::time::{Duration, Instant};
+
+use dotsnboxes::bitboard::BitBoard;
+use dotsnboxes::entities::{Board, Edge, MinMax, Player};
+use dotsnboxes::search::{self, SearchContext};
+use dotsnboxes::transposition::TranspositionTable;
+
+#[test]
+fn test_context_counts_nodes() {
+    let board = Board::new(3, 3).unwrap();
+    let mut table = TranspositionTable::default();
+    let mut context = SearchContext::new(&mut table, None);
+
+    MinMax::alphabeta_with_context(&board, &mut context, 2, i32::MIN, i32::MAX, true);
+
+    assert!(context.get_nodes() > 12);
+    assert!(!context.is_aborted());
+}
+
+#[test]
+fn test_context_aborts_after_deadline() {
+    let board = Board::new(6, 6).unwrap();
+    let mut table = TranspositionTable::default();
+    let mut context = SearchContext::new(&mut table, Some(Instant::now()));
+
+    MinMax::alphabeta_with_context(&board, &mut context, 8, i32::MIN, i32::MAX, true);
+
+    assert!(context.is_aborted());
+}
+
+#[test]
+fn test_deadline_after() {
+    let before = Instant::now();
+    let deadline = search::deadline_after(Duration::from_millis(100)).unwrap();
+
+    assert!(deadline >= before + Duration::from_millis(100));
+    assert_eq!(search::deadline_after(Duration::MAX), None);
+}
+
+#[test]
+fn test_iterative_deepening_respects_time_limit() {
+    let board = Board::new(6, 6).unwrap();
+    let start = Instant::now();
+    let (child, _, depth) = MinMax::iterative_deepening(&board, Duration::from_millis(200));
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(depth >= 1);
+    assert_eq!(1, child.get_lines().len());
+}
+
+#[test]
+fn test_iterative_deepening_finishes_small_endgame() {
+    let board = Board::from_position_str("3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2").unwrap();
+    let (child, value, depth) = MinMax::iterative_deepening(&board, Duration::from_secs(10));
+
+    assert_eq!(2, depth);
+    assert_eq!(2, value);
+    assert!(child.contains_edge(&Edge::vertical(0, 1)));
+    assert_eq!(1, child.get_marked_by_player_2().len());
+}
+
+// Searches the position to the given depth and returns the best edge, its value
+// and the number of nodes visited.
+fn count_nodes(position: &str, depth: u8, move_ordering: bool) -> (Option<usize>, i32, u64) {
+    let board = Board::from_position_str(position).unwrap();
+    let mut bitboard = BitBoard::from_board(&board);
+    let mut table = TranspositionTable::default();
+    let mut context = SearchContext::new(&mut table, None);
+    context.set_move_ordering(move_ordering);
+
+    let is_max = board.get_current_player() == Player::Player2;
+    let (best, value) = MinMax::search(&mut bitboard, &mut context, depth, i32::MIN, i32::MAX, is_max);
+    (best, value, context.get_nodes())
+}
+
+// Plain minimax with the leaf values of MinMax::search, without any pruning.
+fn minimax(board: &mut BitBoard, depth: u8, is_max: bool) -> i32 {
+    if board.is_complete() || depth == 0 {
+        return if is_max {
+            board.get_score(Player::Player2) as i32
+        } else {
+            -(board.get_score(Player::Player1) as i32)
+        };
+    }
+
+    let values = board.get_free_edges().into_iter().map(|edge| {
+        board.make_move(edge);
+        let value = minimax(board, depth - 1, board.get_current_player() == Player::Player2);
+        board.unmake_move();
+        value
+    });
+
+    if is_max {
+        values.max().unwrap()
+    } else {
+        values.min().unwrap()
+    }
+}
+
+#[test]
+fn test_move_ordering_reduces_nodes() {
+    // the node counts before moves were ordered were 12573, 282503, 106813 and 219792
+    let positions = [
+        ("3x3|||1", 6, 2000),
+        ("4x4|||1", 5, 10000),
+        ("4x4|h0,0=1 v1,2=2 h3,1=1 v2,0=2||1", 5, 5000),
+        ("5x4|h0,0=1 h0,3=2 v1,1=1 v2,4=2 h2,2=1 v0,2=2 h3,0=1 v1,3=2||1", 5, 8000),
+    ];
+
+    for (position, depth, limit) in positions.iter() {
+        let (ordered_best, ordered_value, ordered_nodes) = count_nodes(position, *depth, true);
+        let (best, value, nodes) = count_nodes(position, *depth, false);
+
+        assert_eq!((ordered_best, ordered_value), (best, value), "{}", position);
+        assert!(ordered_nodes < nodes, "{}: {} nodes ordered, {} unordered", position, ordered_nodes, nodes);
+        assert!(ordered_nodes < *limit, "{}: {} nodes", position, ordered_nodes);
+    }
+}
+
+#[test]
+fn test_cutoffs_keep_minimax_values() {
+    let positions = ["3x2|||1", "3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2",
+        "3x3|h0,0=1 h0,1=2 v0,0=1 v1,0=2 h2,0=1 v1,2=2 h1,1=1||2"];
+
+    for position in positions.iter() {
+        let board = Board::from_position_str(position).unwrap();
+        let is_max = board.get_current_player() == Player::Player2;
+
+        for depth in 1..=4 {
+            for move_ordering in [true, false].iter() {
+                let (_, value, _) = count_nodes(position, depth, *move_ordering);
+                assert_eq!(value, minimax(&mut BitBoard::from_board(&board), depth, is_max), "{} at depth {}", position, depth);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_captures_first_and_sacrifices_last() {
+    // the box at 0,0 has three sides drawn and the one at 0,1 has two
+    let board = Board::from_position_str("3x2|h0,0=1 v0,0=2 h1,0=1 h0,1=2||1").unwrap();
+    let bitboard = BitBoard::from_board(&board);
+    let mut table = TranspositionTable::default();
+    let context = SearchContext::new(&mut table, None);
+
+    let children = MinMax::order_moves(&bitboard, &context, None);
+    assert_eq!(bitboard.get_edge(children[0]), Edge::vertical(0, 1));
+    assert_eq!(bitboard.get_edge(*children.last().unwrap()), Edge::vertical(0, 2));
+
+    // the move the table remembers goes before everything else
+    let hash_move = bitboard.get_edge_index(&Edge::horizontal(2, 1));
+    assert_eq!(MinMax::order_moves(&bitboard, &context, Some(hash_move))[0], hash_move);
+}
+
+#[test]
+fn test_cutoffs_update_killers_and_history() {
+    let mut table = TranspositionTable::default();
+    let mut context = SearchContext::new(&mut table, None);
+    assert_eq!(context.get_killers(3), [None, None]);
+
+    context.add_cutoff(3, 5, 2);
+    context.add_cutoff(3, 7, 3);
+    context.add_cutoff(3, 7, 1);
+    assert_eq!(context.get_killers(3), [Some(7), Some(5)]);
+    assert_eq!(context.get_history(7), 10);
+    assert_eq!(context.get_history(5), 4);
+    assert_eq!(context.get_history(6), 0);
+
+    // a searched position leaves killers behind
+    let board = Board::new(3, 3).unwrap();
+    let mut table = TranspositionTable::default();
+    let mut context = SearchContext::new(&mut table, None);
+    MinMax::search(&mut BitBoard::from_board(&board), &mut context, 4, i32::MIN, i32::MAX, false);
+    assert!((0..4).any(|ply| context.get_killers(ply)[0].is_some()));
+}
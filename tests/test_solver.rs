@@ -0,0 +1,139 @@
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use dotsnboxes::engine::{Engine, SolverEngine};
+use dotsnboxes::entities::{Board, Edge, MinMax, Player};
+use dotsnboxes::error::Error;
+use dotsnboxes::solver::Solver;
+
+// The final margin for the player to move, tried move by move without any memory.
+fn get_naive_margin(board: &Board) -> i32 {
+    let player = board.get_current_player();
+    let taken = board.get_marked_by_player_1().len() as i32 - board.get_marked_by_player_2().len() as i32;
+    let taken = if player == Player::Player1 { taken } else { -taken };
+
+    if board.is_complete() {
+        return taken;
+    }
+
+    board.get_free_edges().into_iter()
+        .map(|edge| {
+            let mut next = board.clone();
+            next.play(edge).unwrap();
+            let margin = get_naive_margin(&next);
+            if next.get_current_player() == player { margin } else { -margin }
+        })
+        .max()
+        .unwrap()
+}
+
+fn get_random_position(seed: u64, remaining: usize) -> Board {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut board = Board::new(3, 3).unwrap();
+
+    while board.get_free_edges().len() > remaining {
+        let edge = *board.get_free_edges().choose(&mut rng).unwrap();
+        board.play(edge).unwrap();
+    }
+
+    board
+}
+
+#[test]
+fn test_single_box() {
+    let mut solver = Solver::default();
+
+    let empty = Board::new(2, 2).unwrap();
+    assert_eq!(solver.solve(&empty).unwrap(), -1);
+
+    let last = Board::from_position_str("2x2|h0,0=1 h1,0=2 v0,0=1||2").unwrap();
+    assert_eq!(solver.solve(&last).unwrap(), 1);
+    assert_eq!(solver.solve_for(&last, Player::Player1).unwrap(), -1);
+}
+
+#[test]
+fn test_matches_naive_search() {
+    let mut solver = Solver::default();
+
+    for seed in 0..20 {
+        let board = get_random_position(seed, 7);
+        assert_eq!(solver.solve(&board).unwrap(), get_naive_margin(&board), "{}", board.to_position_str());
+    }
+}
+
+#[test]
+fn test_best_move_reaches_margin() {
+    let mut solver = Solver::default();
+    let board = get_random_position(3, 9);
+
+    let margin = solver.solve(&board).unwrap();
+    let (edge, value) = solver.best_move(&board).unwrap();
+    assert_eq!(value, margin);
+
+    let moves = solver.solve_moves(&board).unwrap();
+    assert_eq!(moves.len(), 9);
+    assert!(moves.contains(&(edge, value)));
+    assert!(moves.iter().all(|(_, other)| *other <= margin));
+}
+
+#[test]
+fn test_mirrored_positions_share_entries() {
+    let mut solver = Solver::default();
+
+    let left = Board::from_position_str("3x3|h0,0=1 v0,0=2 v1,0=1||2").unwrap();
+    let right = Board::from_position_str("3x3|h0,1=1 v0,2=2 v1,2=1||2").unwrap();
+
+    let margin = solver.solve(&left).unwrap();
+    let size = solver.get_table_size();
+
+    assert_eq!(solver.solve(&right).unwrap(), margin);
+    assert_eq!(solver.get_table_size(), size);
+}
+
+#[test]
+fn test_refuses_large_positions() {
+    let mut solver = Solver::new(10);
+    let board = Board::new(3, 3).unwrap();
+
+    assert!(!solver.can_solve(&board));
+    assert!(solver.solve(&board).is_err());
+}
+
+#[test]
+fn test_solver_engine_takes_box() {
+    let board = Board::from_position_str("3x2|h0,0=1 h1,0=2 v0,0=1 h0,1=2 h1,1=1||2").unwrap();
+    let mut engine = SolverEngine::new(Solver::default(), MinMax::new());
+
+    assert_eq!(engine.choose_move(&board, Player::Player2).unwrap(), Edge::vertical(0, 1));
+}
+
+#[test]
+fn test_stopped_solve_gives_up() {
+    let mut solver = Solver::default();
+    let board = Board::new(4, 3).unwrap();
+
+    let stopped = solver.solve_moves_with_stop(&board, None, &AtomicBool::new(true));
+    assert!(matches!(stopped, Err(Error::Stopped)));
+    let expired = solver.best_move_with_stop(&board, Some(Instant::now()), &AtomicBool::new(false));
+    assert!(matches!(expired, Err(Error::Stopped)));
+
+    // the aborted searches left nothing wrong behind
+    let mut later = board.clone();
+    for edge in board.get_free_edges().into_iter().step_by(2).take(9) {
+        later.play(edge).unwrap();
+    }
+    assert_eq!(solver.solve(&later).unwrap(), get_naive_margin(&later));
+}
+
+#[test]
+fn test_solver_engine_stops() {
+    let board = Board::new(4, 3).unwrap();
+    let mut engine = SolverEngine::new(Solver::default(), MinMax::new());
+
+    let result = engine.choose_move_with_stop(&board, Player::Player1, &AtomicBool::new(true));
+    assert!(matches!(result, Err(Error::Stopped)));
+}
@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use dotsnboxes::engine::{EngineKind, GreedyEngine, RandomEngine};
+use dotsnboxes::entities::{Board, Player, DEFAULT_TIME_LIMIT_MILLIS};
+use dotsnboxes::tournament::{self, play_game, EngineConfig, Tournament};
+
+#[test]
+fn test_parse_engine_config() {
+    let config: EngineConfig = "minmax:4@500".parse().unwrap();
+    assert_eq!(config, EngineConfig::new(EngineKind::MinMax, 4, Duration::from_millis(500)));
+    assert_eq!(config.to_string(), "minmax:4@500");
+
+    let config: EngineConfig = "greedy".parse().unwrap();
+    assert_eq!(config.get_max_depth(), u8::MAX);
+    assert_eq!(config.get_time_limit(), Duration::from_millis(DEFAULT_TIME_LIMIT_MILLIS));
+    assert_eq!(config.to_string(), "greedy");
+
+    assert!("minmax:x".parse::<EngineConfig>().is_err());
+    assert!("minmax:0".parse::<EngineConfig>().is_err());
+    assert!("nothing@100".parse::<EngineConfig>().is_err());
+}
+
+#[test]
+fn test_huge_time_limit_means_no_limit() {
+    let board = Board::new(3, 3).unwrap();
+
+    for config in ["minmax:2@18446744073709551615", "minmax:2@18446744073709551615/2"].iter() {
+        let mut engine = config.parse::<EngineConfig>().unwrap().create_engine();
+        let edge = engine.choose_move(&board, Player::Player1).unwrap();
+        assert!(board.get_free_edges().contains(&edge));
+    }
+}
+
+#[test]
+fn test_play_game_fills_board() {
+    let mut greedy = GreedyEngine::with_seed(1);
+    let mut random = RandomEngine::with_seed(2);
+
+    let summary = play_game(3, 3, &mut greedy, &mut random).unwrap();
+
+    assert_eq!(summary.get_score(Player::Player1) + summary.get_score(Player::Player2), 4);
+    assert_eq!(summary.get_moves(Player::Player1) + summary.get_moves(Player::Player2), 12);
+    assert_eq!(summary.get_margin(Player::Player1), -summary.get_margin(Player::Player2));
+}
+
+#[test]
+fn test_tournament_results_mirror_each_other() {
+    let engines = vec!["greedy".parse().unwrap(), "random".parse().unwrap(), "chains".parse().unwrap()];
+    let results = Tournament::new(engines, 3, 3, 4).run().unwrap();
+
+    assert_eq!(results.len(), 6);
+    for result in &results {
+        assert_eq!(result.get_games(), 4);
+
+        let mirror = results.iter()
+            .find(|other| other.get_engine() == result.get_opponent() && other.get_opponent() == result.get_engine())
+            .unwrap();
+        assert_eq!(result.get_wins(), mirror.get_losses());
+        assert_eq!(result.get_draws(), mirror.get_draws());
+        assert!((result.get_average_margin() + mirror.get_average_margin()).abs() < 1e-9);
+    }
+
+    assert_eq!(results[0].get_engine().get_engine(), EngineKind::Greedy);
+    assert_eq!(results[0].get_opponent().get_engine(), EngineKind::Random);
+}
+
+#[test]
+fn test_csv_and_json_output() {
+    let engines = vec!["greedy".parse().unwrap(), "random".parse().unwrap()];
+    let results = Tournament::new(engines, 3, 2, 2).run().unwrap();
+
+    let csv = tournament::to_csv(&results);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "size,engine,opponent,games,wins,draws,losses,average_margin,average_move_ms");
+    assert!(lines[1].starts_with("3x2,greedy,random,2,"));
+
+    let json = tournament::to_json(&results);
+    assert!(json.starts_with('[') && json.ends_with(']'));
+    assert_eq!(json.matches("\"engine\"").count(), 2);
+    assert_eq!(tournament::to_json(&[]), "[]");
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use dotsnboxes::bitboard::BitBoard;
+use dotsnboxes::engine::Engine;
+use dotsnboxes::entities::{Board, Edge, MinMax, Player};
+use dotsnboxes::transposition::{Bound, Entry, TranspositionTable, Zobrist};
+
+#[test]
+fn test_zobrist_is_deterministic() {
+    let first = Zobrist::new(3, 3);
+    let second = Zobrist::new(3, 3);
+
+    assert_eq!(first.get_edge(11), second.get_edge(11));
+    assert_eq!(first.get_square_2(3), second.get_square_2(3));
+    assert_ne!(first.get_edge(0), first.get_edge(1));
+}
+
+#[test]
+fn test_board_sizes_hash_apart() {
+    let small = Zobrist::new(3, 3);
+    let large = Zobrist::new(6, 6);
+    let wide = Zobrist::new(4, 3);
+    let tall = Zobrist::new(3, 4);
+
+    assert_ne!(small.get_edge(0), large.get_edge(0));
+    assert_ne!(wide.get_edge(0), tall.get_edge(0));
+
+    let empty_small = BitBoard::from_board(&Board::new(3, 3).unwrap()).get_hash();
+    let empty_large = BitBoard::from_board(&Board::new(6, 6).unwrap()).get_hash();
+    assert_ne!(empty_small, 0);
+    assert_ne!(empty_small, empty_large);
+}
+
+#[test]
+fn test_minmax_table_serves_boards_of_any_size() {
+    let mut minmax = MinMax::with_limits(Duration::from_secs(10), 3);
+
+    for (width, height) in [(3, 3), (6, 6), (3, 3), (5, 2)].iter() {
+        let board = Board::new(*width, *height).unwrap();
+        let edge = minmax.choose_move(&board, Player::Player1).unwrap();
+        assert!(board.get_free_edges().contains(&edge));
+    }
+}
+
+#[test]
+fn test_hash_transpositions() {
+    let board = Board::new(4, 4).unwrap();
+    let mut first = BitBoard::from_board(&board);
+    let mut second = BitBoard::from_board(&board);
+    let a = board.get_edge_index(&Edge::horizontal(0, 0));
+    let b = board.get_edge_index(&Edge::vertical(2, 3));
+    let c = board.get_edge_index(&Edge::horizontal(3, 1));
+
+    let initial = first.get_hash();
+    first.make_move(a);
+    first.make_move(b);
+    first.make_move(c);
+    second.make_move(c);
+    second.make_move(b);
+    second.make_move(a);
+
+    assert_eq!(first.get_hash(), second.get_hash());
+
+    first.unmake_move();
+    first.unmake_move();
+    first.unmake_move();
+    assert_eq!(initial, first.get_hash());
+}
+
+#[test]
+fn test_hash_matches_board() {
+    let mut board = Board::new(3, 2).unwrap();
+    let mut bitboard = BitBoard::from_board(&board);
+
+    for edge in &[Edge::horizontal(0, 0), Edge::horizontal(1, 0), Edge::vertical(0, 0), Edge::vertical(0, 1)] {
+        board.play(*edge).unwrap();
+        bitboard.make_move(board.get_edge_index(edge));
+        assert_eq!(BitBoard::from_board(&board).get_hash(), bitboard.get_hash());
+    }
+
+    assert_eq!(Player::Player2, bitboard.get_current_player());
+}
+
+#[test]
+fn test_table_store_and_probe() {
+    let mut table = TranspositionTable::new(16);
+
+    assert!(table.probe(42).is_none());
+    table.store(Entry::new(42, 3, 7, Bound::Exact, Some(1)));
+
+    let entry = table.probe(42).unwrap();
+    assert_eq!(7, entry.get_value());
+    assert_eq!(Bound::Exact, entry.get_bound());
+    assert_eq!(Some(1), entry.get_best());
+
+    table.store(Entry::new(42, 2, 9, Bound::Lower, Some(2)));
+    assert_eq!(7, table.probe(42).unwrap().get_value());
+
+    table.store(Entry::new(58, 1, 3, Bound::Upper, None));
+    assert!(table.probe(42).is_none());
+
+    assert_eq!(4, table.get_probes());
+    assert_eq!(2, table.get_hits());
+    assert_eq!(2, table.get_stores());
+    assert!((table.get_hit_rate() - 0.5).abs() < 1e-9);
+
+    table.clear();
+    assert!(table.probe(58).is_none());
+    assert_eq!(1, table.get_probes());
+}
+
+#[test]
+fn test_search_with_table() {
+    let board = Board::from_position_str("4x3|h0,0=1 h0,1=2 h0,2=1 v0,0=2 v1,3=1 h2,0=2 h2,2=1 v1,1=2||1").unwrap();
+
+    let mut table = TranspositionTable::default();
+    let with_table = MinMax::alphabeta_with_table(&board, &mut table, 9, i32::MIN, i32::MAX, true);
+    let without_table = MinMax::alphabeta_with_table(&board, &mut TranspositionTable::new(1), 9, i32::MIN, i32::MAX, true);
+
+    assert_eq!(without_table.1, with_table.1);
+    assert!(table.get_hits() > 0);
+    assert!(table.get_hit_rate() > 0.0);
+}
+
+#[test]
+fn test_table_sized_to_search() {
+    // 1 + 12 + 66 positions with up to two of twelve edges drawn
+    assert_eq!(TranspositionTable::for_search(12, 2).get_size(), 79);
+    assert_eq!(TranspositionTable::for_search(3, 10).get_size(), 8);
+    assert_eq!(TranspositionTable::for_search(0, 4).get_size(), 1);
+    assert_eq!(TranspositionTable::for_search(60, 8).get_size(), TranspositionTable::default().get_size());
+}
+
+#[test]
+fn test_minmax_keeps_table_between_moves() {
+    let board = Board::new(3, 3).unwrap();
+    let mut minmax = MinMax::with_limits(Duration::from_secs(10), 3);
+
+    minmax.choose_move(&board, Player::Player1).unwrap();
+    let stores = minmax.get_table().get_stores();
+    assert!(stores > 0);
+
+    minmax.choose_move(&board, Player::Player1).unwrap();
+    assert!(minmax.get_table().get_probes() > 0);
+    assert!(minmax.get_table().get_stores() >= stores);
+}
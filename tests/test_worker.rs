@@ -0,0 +1,94 @@
+use std::thread;
+use std::time::Duration;
+
+use dotsnboxes::engine::RandomEngine;
+use dotsnboxes::entities::{Board, Edge, MinMax};
+use dotsnboxes::worker::SearchWorker;
+
+#[test]
+fn test_worker_delivers_move() {
+    let mut board = Board::new(3, 3).unwrap();
+    board.play(Edge::horizontal(0, 0)).unwrap();
+
+    let engine = MinMax::with_time_limit(Duration::from_millis(100));
+    let mut worker = SearchWorker::start(board.clone(), Box::new(engine));
+
+    let mut result = None;
+    for _ in 0..500 {
+        result = worker.poll();
+        if result.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let edge = result.unwrap().unwrap();
+    assert!(board.play(edge).is_ok());
+    assert!(worker.is_finished());
+}
+
+#[test]
+fn test_worker_hands_engine_back() {
+    let mut board = Board::new(3, 3).unwrap();
+    let engine = MinMax::with_time_limit(Duration::from_millis(100));
+    let mut worker = SearchWorker::start_keeping_engine(board.clone(), Box::new(engine));
+
+    let mut result = None;
+    for _ in 0..500 {
+        result = worker.poll();
+        if result.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let (mut engine, edge) = result.unwrap();
+    board.play(edge.unwrap()).unwrap();
+    assert_eq!(engine.get_name(), "MinMax");
+    assert!(board.get_free_edges().contains(&engine.choose_move(&board, board.get_current_player()).unwrap()));
+}
+
+#[test]
+fn test_cancelled_worker_delivers_nothing() {
+    let board = Board::new(6, 6).unwrap();
+    let engine = MinMax::with_time_limit(Duration::from_secs(60));
+    let mut worker = SearchWorker::start(board, Box::new(engine));
+
+    worker.cancel();
+
+    assert!(worker.is_finished());
+    assert!(worker.poll().is_none());
+}
+
+#[test]
+fn test_worker_runs_any_engine() {
+    let board = Board::new(3, 3).unwrap();
+    let mut worker = SearchWorker::start(board.clone(), Box::new(RandomEngine::with_seed(7)));
+
+    let mut result = None;
+    for _ in 0..500 {
+        result = worker.poll();
+        if result.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(board.get_free_edges().contains(&result.unwrap().unwrap()));
+}
+
+#[test]
+fn test_worker_runs_any_job() {
+    let mut worker = SearchWorker::spawn(|_| 6 * 7);
+
+    let mut result = None;
+    for _ in 0..500 {
+        result = worker.poll();
+        if result.is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(result, Some(42));
+}